@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+use super::consts::system::{BENCH_SEED, DETERMINISTIC_TICKS_PER_SECOND};
+use super::error::Result;
+use super::input::InputSnapshot;
+use super::{GameState, Mode};
+
+/// Simulate `ticks` ticks of a practice run without a window, with the player sitting still, and
+/// print how long it took. Practice mode keeps the run going however many obstacles pile up.
+pub fn run(ticks: u64) -> Result<()> {
+    let mut state = GameState::new_headless(BENCH_SEED);
+    state.mode = Mode::Practice;
+
+    let start = Instant::now();
+    for _ in 0..ticks {
+        tick(&mut state)?;
+    }
+    let elapsed = start.elapsed();
+
+    println!("Simulated {} ticks in {:.3}s", ticks, elapsed.as_secs_f64());
+    println!("  {:.0} ticks/s", ticks as f64 / elapsed.as_secs_f64());
+    println!(
+        "  Peak obstacles: {}",
+        state.peak_obstacles.max().unwrap_or(0)
+    );
+    Ok(())
+}
+
+/// Play `ticks` ticks of a run with the default config without a window, with the player sitting
+/// still, and print the score it ended on along with how long each tick took. Like `run`, this
+/// plays practice mode, so the run never ends early and never goes through the game over screen.
+pub fn run_headless(ticks: u64) -> Result<()> {
+    let mut state = GameState::new_headless(BENCH_SEED);
+    state.mode = Mode::Practice;
+
+    let mut tick_times = Vec::with_capacity(ticks as usize);
+    for _ in 0..ticks {
+        let start = Instant::now();
+        tick(&mut state)?;
+        tick_times.push(start.elapsed());
+    }
+    tick_times.sort();
+
+    println!("Final score: {}", state.player.score);
+    println!("  Ticks: {}", tick_times.len());
+    if let Some(&max) = tick_times.last() {
+        let total: Duration = tick_times.iter().sum();
+        let average = total / tick_times.len() as u32;
+        // The tick 99% of ticks were at least as fast as.
+        let p99 = tick_times[tick_times.len() * 99 / 100];
+        println!(
+            "  Tick time: avg {}us, p99 {}us, max {}us",
+            average.as_micros(),
+            p99.as_micros(),
+            max.as_micros()
+        );
+    }
+    Ok(())
+}
+
+/// Simulate one tick at the deterministic tick rate with no input.
+fn tick(state: &mut GameState) -> Result<()> {
+    let dt = Duration::from_micros(1_000_000 / DETERMINISTIC_TICKS_PER_SECOND);
+    state.tick_count += 1;
+    state.step(dt, InputSnapshot::default())
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+use super::consts::system::CONFIG_PATH;
+use super::error::Result;
+
+/// User settings read from `config.toml`. Any missing key falls back to its default.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Name of the `ColorScheme` to draw with.
+    pub scheme: String,
+    /// Name of the `DifficultyPreset` selected when the game starts.
+    pub difficulty: String,
+    /// Whether to draw a faint grid and a pulsing vignette inside the field.
+    pub field_backdrop: bool,
+    /// Whether an obstacle's warning line lingers and retreats across the field after it leaves.
+    pub exit_sweeps: bool,
+    /// Whether the player comes back in on the far side after moving off an edge of the field.
+    pub wrap_edges: bool,
+    /// Whether dragging with the mouse or a finger moves the player and right-clicking slows
+    /// them down.
+    pub mouse_control: bool,
+    /// Index into `SKINS` of the player's colors. Changed in game with F6.
+    pub skin: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            scheme: "classic".to_owned(),
+            difficulty: "normal".to_owned(),
+            field_backdrop: true,
+            exit_sweeps: true,
+            wrap_edges: false,
+            mouse_control: true,
+            skin: 0,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Config> {
+        let text = fs::read_to_string(CONFIG_PATH)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    /// Write the config back to `CONFIG_PATH`. Any comments in the file are lost.
+    pub fn save(&self) -> Result<()> {
+        fs::write(CONFIG_PATH, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Load the config, falling back to the defaults if there is no config file or it can't be
+    /// read.
+    pub fn load_or_default() -> Config {
+        Config::load().unwrap_or_else(|e| {
+            if !e.is_not_found() {
+                eprintln!("Could not load {}: {}", CONFIG_PATH, e);
+            }
+            Config::default()
+        })
+    }
+}
@@ -1,32 +1,522 @@
 pub mod game {
     pub const PLAYER_SPEED: f32 = 5.0;
     pub const PLAYER_SLOWMO_FACTOR: f32 = 2.2;
+    /// Speed gained per tick while a movement key is held.
+    pub const PLAYER_ACCELERATION: f32 = 1.5;
+    /// Fraction of the player's velocity kept each tick.
+    pub const PLAYER_FRICTION: f32 = 0.8;
+    /// Length of each of the player's edges.
+    pub const PLAYER_SIZE: f32 = 50.0;
     pub const COLLECTOR_EDGE_LENGTH: f32 = 80.0;
-    pub const FIELD_EDGE_LENGTH: f32 = 500.0;
+    /// The collector is centered on the player, and touching the player itself is a hit rather
+    /// than a graze. A collector no bigger than the player could never graze anything.
+    const _: () = assert!(
+        COLLECTOR_EDGE_LENGTH > PLAYER_SIZE,
+        "COLLECTOR_EDGE_LENGTH must be bigger than PLAYER_SIZE"
+    );
+    pub const FIELD_WIDTH: f32 = 500.0;
+    pub const FIELD_HEIGHT: f32 = 500.0;
+    /// The player has to be small next to the field, or there would be no room to get out of an
+    /// obstacle's way.
+    const _: () = assert!(
+        PLAYER_SIZE < FIELD_WIDTH / 4. && PLAYER_SIZE < FIELD_HEIGHT / 4.,
+        "PLAYER_SIZE must be under a quarter of each of the field's dimensions"
+    );
+    /// The larger of the field's two dimensions.
+    pub const FIELD_LONGEST_EDGE: f32 = if FIELD_WIDTH > FIELD_HEIGHT {
+        FIELD_WIDTH
+    } else {
+        FIELD_HEIGHT
+    };
+    /// Number of rixels around the field's border.
+    pub const FIELD_PERIMETER: f32 = (FIELD_WIDTH + FIELD_HEIGHT) * 2.;
     const OBSTACLE_WARNING_DRAW_TIME: u32 = 20;
     pub const OBSTACLE_WARNING_FINISH_WAIT_TIME: u32 = 20;
     pub const OBSTACLE_PRE_SPAWN_WARN_TIME: u32 =
         OBSTACLE_WARNING_DRAW_TIME + OBSTACLE_WARNING_FINISH_WAIT_TIME;
     pub const OBSTACLE_HIDE_DELAY: u32 = 20;
-    pub const OBSTACLE_WARNING_MOVE_SPEED: f32 =
-        FIELD_EDGE_LENGTH / OBSTACLE_WARNING_DRAW_TIME as f32;
-    pub const SPAWN_RATE_FACTOR: f32 = 6.;
-    pub const SPAWN_RATE_SUBTRACT: f32 = 1.2;
+    /// Boss obstacles are telegraphed for longer, so the player has time to get out of the way.
+    pub const BOSS_PRE_SPAWN_WARN_TIME: u32 = OBSTACLE_PRE_SPAWN_WARN_TIME * 3;
+    pub const OBSTACLE_WARNING_MOVE_SPEED: f32 = warning_move_speed();
+    /// The warning line has to cross the whole field in exactly its draw time.
+    const _: () = assert!(
+        OBSTACLE_WARNING_MOVE_SPEED * OBSTACLE_WARNING_DRAW_TIME as f32 == FIELD_LONGEST_EDGE
+    );
+    pub const RESUME_COUNTDOWN_MS: u64 = 1000;
+    pub const INITIAL_SPAWN_INTERVAL_MS: u64 = 4000;
+    /// The spawn interval never drops below this, however high the score gets.
+    pub const MIN_SPAWN_INTERVAL_MS: u64 = 200;
+    /// A screen flash marks every time the score passes a multiple of this.
+    pub const SCORE_MILESTONE_INTERVAL: u32 = 1000;
+    /// The player gets `SURVIVAL_MILESTONE_POINTS` every time they have survived another this
+    /// many seconds.
+    pub const SURVIVAL_MILESTONE_SECS: u64 = 30;
+    pub const SURVIVAL_MILESTONE_POINTS: u32 = 250;
+    /// The player earns a bomb every time their score passes a multiple of this.
+    pub const BOMB_SCORE_INTERVAL: u32 = 2000;
+    /// The player gets a magnet every time their score passes a multiple of this.
+    pub const MAGNET_SCORE_INTERVAL: u32 = 3000;
+    /// How long a magnet lasts. Another magnet while one is active adds this much again.
+    pub const MAGNET_DURATION_MS: u64 = 10_000;
+    /// How much bigger a magnet makes the collector.
+    pub const MAGNET_COLLECTOR_FACTOR: f32 = 2.0;
+    /// The collector shrinks back to its usual size over the last this many ms of a magnet.
+    pub const MAGNET_SHRINK_MS: u64 = 500;
+    /// With camping pressure on, a player who stays within `CAMPING_RADIUS` of one spot for this
+    /// long starts losing points.
+    pub const CAMPING_WINDOW_MS: u64 = 2000;
+    pub const CAMPING_RADIUS: f32 = 5.0;
+    /// Points lost per second while camping.
+    pub const CAMPING_DRAIN_PER_SEC: f32 = 10.0;
+    /// How far the player has to move to get past the tutorial's first step.
+    pub const TUTORIAL_MOVE_DISTANCE: f32 = 100.0;
+    /// Ticks of slow-mo the tutorial asks for before moving on.
+    pub const TUTORIAL_SLOWMO_TICKS: u32 = 60;
+    /// How long the player can be stuck on a tutorial step before it offers a hint.
+    pub const TUTORIAL_HINT_DELAY_MS: u64 = 8000;
+    /// Slow-mo stamina, on a 0 to 1 scale, used up per tick of slow movement.
+    pub const STAMINA_DRAIN_PER_TICK: f32 = 0.01;
+    /// Stamina regained per tick while not in slow-mo.
+    pub const STAMINA_REGEN_PER_TICK: f32 = 0.004;
+    /// Once stamina runs out, slow-mo stays unavailable until it recharges past this.
+    pub const STAMINA_RECOVER_THRESHOLD: f32 = 0.3;
+    /// Gamepad stick tilt below this is ignored.
+    pub const GAMEPAD_STICK_DEAD_ZONE: f32 = 0.3;
+    /// The player stops once they are this close to where they are being dragged to, so they
+    /// don't jitter back and forth over it.
+    pub const TOUCH_DEAD_ZONE: f32 = 10.0;
+
+    pub const OBSTACLE_BASE_LENGTH: f32 = 300.0;
+    /// Most obstacles allowed on the field at once on normal difficulty. The other presets
+    /// adjust this a little.
+    pub const MAX_OBSTACLES: usize = 24;
+    /// Size of the cells obstacles are bucketed into for collision checks. About the collector's
+    /// size, so it only ever covers a handful of cells.
+    pub const COLLISION_GRID_CELL_SIZE: f32 = 100.0;
+    pub const OBSTACLE_DESPAWN_POINTS: u32 = 100;
+    pub const FAST_OBSTACLE_SPEED_FACTOR: f32 = 2.0;
+    pub const FAST_OBSTACLE_LENGTH_FACTOR: f32 = 0.5;
+    pub const WIDE_OBSTACLE_WIDTH_FACTOR: f32 = 3.0;
+    pub const WIDE_OBSTACLE_SPEED_FACTOR: f32 = 0.6;
+    pub const BONUS_OBSTACLE_POINTS_FACTOR: u32 = 5;
+    pub const ACCELERATING_OBSTACLE_START_SPEED_FACTOR: f32 = 0.5;
+    /// How much an accelerating obstacle's speed goes up each tick.
+    pub const ACCELERATING_OBSTACLE_ACCELERATION: f32 = 0.05;
+    /// How many ticks a blocker obstacle stays parked in the middle of the field.
+    pub const BLOCKER_OBSTACLE_DWELL_TICKS: f32 = 180.0;
+    /// A boss obstacle comes in each time the score passes a multiple of this.
+    pub const BOSS_SCORE_INTERVAL: u32 = 5000;
+    /// How much of its side a boss obstacle covers.
+    pub const BOSS_OBSTACLE_WIDTH_FACTOR: f32 = 0.5;
+    pub const BOSS_OBSTACLE_SPEED_FACTOR: f32 = 0.5;
+    /// Awarded for outlasting a boss obstacle, in place of the usual despawn points.
+    pub const BOSS_SURVIVAL_POINTS: u32 = 1000;
+    /// Awarded when a gold obstacle despawns, in place of the usual despawn points.
+    pub const GOLD_OBSTACLE_DESPAWN_POINTS: u32 = 500;
+    /// Points per tick of grazing a gold obstacle. Every other kind gives one.
+    pub const GOLD_OBSTACLE_GRAZE_POINTS: u32 = 3;
+    /// How far an obstacle in a wave may stray from its even spacing, as a fraction of the gap
+    /// between neighbours.
+    pub const SPAWN_WAVE_JITTER: f32 = 0.25;
+    /// Spawn weights for each obstacle kind, in the order Normal, Fast, Wide, Bonus, Accelerating,
+    /// Blocker, Gold, Boss. The weight used is `BASE + PER_1000_SCORE * score / 1000`. Gold keeps
+    /// to roughly one spawn in ten at any score.
+    pub const OBSTACLE_KIND_WEIGHTS_BASE: [f32; 8] = [20.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.3, 0.0];
+    pub const OBSTACLE_KIND_WEIGHTS_PER_1000_SCORE: [f32; 8] =
+        [0.0, 1.0, 1.0, 0.1, 0.5, 0.3, 0.3, 0.0];
+
+    /// How far the obstacle warning line moves per tick, so that it crosses the field the long
+    /// way in `OBSTACLE_WARNING_DRAW_TIME` ticks.
+    pub const fn warning_move_speed() -> f32 {
+        FIELD_LONGEST_EDGE / OBSTACLE_WARNING_DRAW_TIME as f32
+    }
+}
+
+pub mod difficulty {
+    use serde::{Deserialize, Serialize};
+    use std::{cmp, time::Duration};
+
+    use super::game::{MAX_OBSTACLES, MIN_SPAWN_INTERVAL_MS};
+
+    /// Obstacle speed gained per 1000 points, up to the preset's `obstacle_speed_max`.
+    pub const OBSTACLE_SPEED_PER_1000_SCORE: f32 = 0.5;
+    /// With adaptive spawning, the weight of the side nearest the player is multiplied by this.
+    pub const ADAPTIVE_SIDE_BIAS: f32 = 3.0;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub enum DifficultyPreset {
+        Easy,
+        Normal,
+        Hard,
+        /// One life, and obstacles keep getting faster for as long as the run lasts.
+        Endless,
+    }
+
+    impl DifficultyPreset {
+        pub const ALL: [DifficultyPreset; 4] = [
+            DifficultyPreset::Easy,
+            DifficultyPreset::Normal,
+            DifficultyPreset::Hard,
+            DifficultyPreset::Endless,
+        ];
+
+        /// Look up a preset by the name used in `config.toml`.
+        pub fn from_name(name: &str) -> Option<Self> {
+            match name {
+                "easy" => Some(DifficultyPreset::Easy),
+                "normal" => Some(DifficultyPreset::Normal),
+                "hard" => Some(DifficultyPreset::Hard),
+                "endless" => Some(DifficultyPreset::Endless),
+                _ => None,
+            }
+        }
+
+        pub fn name(self) -> &'static str {
+            match self {
+                DifficultyPreset::Easy => "Easy",
+                DifficultyPreset::Normal => "Normal",
+                DifficultyPreset::Hard => "Hard",
+                DifficultyPreset::Endless => "Endless",
+            }
+        }
+
+        pub fn settings(self) -> DifficultySettings {
+            match self {
+                DifficultyPreset::Easy => DifficultySettings::easy(),
+                DifficultyPreset::Normal => DifficultySettings::normal(),
+                DifficultyPreset::Hard => DifficultySettings::hard(),
+                DifficultyPreset::Endless => DifficultySettings::endless(),
+            }
+        }
+    }
+
+    /// Everything that makes a run harder or easier.
+    #[derive(Debug, Clone, Copy)]
+    pub struct DifficultySettings {
+        pub spawn_rate_factor: f32,
+        pub spawn_rate_subtract: f32,
+        /// The spawn interval never drops below this, however high the score gets.
+        pub min_spawn_interval: Duration,
+        pub obstacle_speed_base: f32,
+        pub obstacle_speed_max: f32,
+        /// Hits the player can take before the run ends. 0 disables lives, so the first hit ends
+        /// the run and no life counter is shown.
+        pub player_lives: u8,
+        /// Relative chance of an obstacle spawning from each side, in the order North, East,
+        /// South, West.
+        pub side_weights: [f32; 4],
+        /// Favor spawning from whichever side the player is nearest to.
+        pub adaptive_sides: bool,
+        /// Spawning is skipped while this many obstacles are on the field.
+        pub max_obstacles: usize,
+        /// Drain the score while the player stays in one spot. See `CAMPING_WINDOW_MS`.
+        pub camping_pressure: bool,
+    }
+
+    impl DifficultySettings {
+        pub fn easy() -> Self {
+            DifficultySettings {
+                spawn_rate_factor: 8.,
+                spawn_rate_subtract: 1.2,
+                min_spawn_interval: Duration::from_millis(MIN_SPAWN_INTERVAL_MS),
+                obstacle_speed_base: 2.5,
+                obstacle_speed_max: 4.,
+                player_lives: 3,
+                side_weights: [1.; 4],
+                adaptive_sides: false,
+                max_obstacles: MAX_OBSTACLES - 4,
+                camping_pressure: false,
+            }
+        }
+
+        pub fn normal() -> Self {
+            DifficultySettings {
+                spawn_rate_factor: 6.,
+                spawn_rate_subtract: 1.2,
+                min_spawn_interval: Duration::from_millis(MIN_SPAWN_INTERVAL_MS),
+                obstacle_speed_base: 3.,
+                obstacle_speed_max: 5.,
+                player_lives: 1,
+                side_weights: [1.; 4],
+                adaptive_sides: false,
+                max_obstacles: MAX_OBSTACLES,
+                camping_pressure: true,
+            }
+        }
+
+        pub fn hard() -> Self {
+            DifficultySettings {
+                spawn_rate_factor: 4.5,
+                spawn_rate_subtract: 1.,
+                min_spawn_interval: Duration::from_millis(MIN_SPAWN_INTERVAL_MS),
+                obstacle_speed_base: 3.5,
+                obstacle_speed_max: 6.5,
+                player_lives: 1,
+                side_weights: [1.; 4],
+                adaptive_sides: true,
+                max_obstacles: MAX_OBSTACLES + 4,
+                camping_pressure: true,
+            }
+        }
+
+        pub fn endless() -> Self {
+            DifficultySettings {
+                obstacle_speed_max: f32::INFINITY,
+                player_lives: 0,
+                ..DifficultySettings::normal()
+            }
+        }
+
+        /// Given the player's current score value, decide how long the wait for the next
+        /// obstacle to spawn should be. Never less than `min_spawn_interval`.
+        pub fn spawn_interval(&self, score: u32) -> Duration {
+            let score = cmp::max(100, score);
+            let spawntime = (self.spawn_rate_factor / (score as f32 / 100.).powf(1. / 3.)
+                - self.spawn_rate_subtract)
+                * 1000.;
+            // Clamp while still a float; the formula goes negative at high scores, and casting a
+            // negative float to u64 is not something to rely on.
+            Duration::from_millis(spawntime.max(self.min_spawn_interval.as_millis() as f32) as u64)
+        }
+
+        /// Base speed of obstacles spawned at the given score.
+        pub fn obstacle_speed(&self, score: u32) -> f32 {
+            (self.obstacle_speed_base + OBSTACLE_SPEED_PER_1000_SCORE * score as f32 / 1000.)
+                .min(self.obstacle_speed_max)
+        }
+    }
 }
 
 pub mod system {
+    pub const GAME_TITLE: &str = "First Game";
+    /// Window size used unless `WIDTH_FLAG` or `HEIGHT_FLAG` says otherwise.
     pub const WIN_WIDTH: u32 = 800;
     pub const WIN_HEIGHT: u32 = 600;
+    /// Command line flags that set the window's size in pixels.
+    pub const WIDTH_FLAG: &str = "--width";
+    pub const HEIGHT_FLAG: &str = "--height";
+    /// Command line flag that starts the game fullscreen. Alt+Enter switches in game.
+    pub const FULLSCREEN_FLAG: &str = "--fullscreen";
+    /// Smallest window the HUD still has room in.
+    pub const MIN_WIN_WIDTH: u32 = 320;
+    pub const MIN_WIN_HEIGHT: u32 = 240;
+    /// Space kept between the playfield and each edge of the window. A window too small for it
+    /// gets a scaled down playfield.
+    pub const PLAYFIELD_MARGIN: f32 = 50.;
     pub const FPS_GRAPH_SAMPLE_COUNT: usize = 64;
+    pub const FPS_UPDATE_INTERVAL_TICKS: u64 = 12;
+    /// Cosmetic effects are dropped once the recent average FPS falls below this.
+    pub const REDUCED_QUALITY_FPS: f64 = 30.;
+    /// Dropped effects come back once the recent average FPS climbs above this.
+    pub const FULL_QUALITY_FPS: f64 = 45.;
+    /// A frame taking this many times longer than the recent average counts as a spike.
+    pub const FRAME_SPIKE_FACTOR: f64 = 2.5;
+    /// How many frame spikes are kept for the dump on exit.
+    pub const FRAME_SPIKE_LOG_SIZE: usize = 64;
+    /// How many obstacle events are kept for the end of `EVENT_LOG_PATH`.
+    pub const OBSTACLE_EVENT_LOG_SIZE: usize = 256;
+    /// How many game events are kept for `EVENT_LOG_PATH`. Older ones are dropped.
+    pub const GAME_EVENT_LOG_SIZE: usize = 10_000;
+    /// How many frames of timings are kept for profiling.
+    pub const DIAGNOSTICS_BUFFER_SIZE: usize = 120;
+    /// How many of the latest frames the F3 readout lists.
+    pub const DIAGNOSTICS_SHOWN_FRAMES: usize = 10;
+    /// Update rate assumed by `deterministic` builds.
+    pub const DETERMINISTIC_TICKS_PER_SECOND: u64 = 60;
+    /// Cells along each side of the debug obstacle heatmap.
+    #[cfg(feature = "debug")]
+    pub const HEATMAP_GRID_SIZE: usize = 50;
 
+    pub const CONFIG_PATH: &str = "config.toml";
+    /// Where the best runs are kept.
+    pub const HIGH_SCORES_PATH: &str = "highscores.json";
+    /// Bumped whenever the high score file's layout changes, so old files are ignored instead of
+    /// misread.
+    pub const HIGH_SCORES_VERSION: u32 = 1;
+    pub const HIGH_SCORE_TABLE_SIZE: usize = 10;
+    /// What each of a high score's initials can be.
+    pub const HIGH_SCORE_INITIALS_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    /// Every error that ends a run is described here, oldest first.
+    pub const CRASH_LOG_PATH: &str = "crash.log";
+    /// Where the most recent finished run is recorded.
+    pub const REPLAY_PATH: &str = "replay_last.bin";
+    /// Where the most recent finished run's game events are written, one JSON object per line,
+    /// followed by its latest obstacle events.
+    pub const EVENT_LOG_PATH: &str = "last_run.jsonl";
+    /// Command line flag that picks the daily mode's seed, e.g. to play an earlier day again.
+    pub const SEED_FLAG: &str = "--seed";
+    pub const SECS_PER_DAY: u64 = 86_400;
+    /// Command line flag that plays back `REPLAY_PATH` instead of showing the title screen.
+    pub const REPLAY_FLAG: &str = "--replay";
+    /// Command line flag that simulates the given number of ticks without a window and prints
+    /// timing stats.
+    pub const BENCH_FLAG: &str = "--bench";
+    /// Command line flag that plays the given number of ticks of a practice run without a window,
+    /// then prints the score and how long the ticks took.
+    pub const HEADLESS_FLAG: &str = "--headless";
+    /// RNG seed for `BENCH_FLAG` and `HEADLESS_FLAG` runs, so every bench simulates the same obstacles.
+    pub const BENCH_SEED: u64 = 0;
+    /// Where F12 saves screenshots.
+    pub const SCREENSHOT_DIR: &str = "screenshots";
+
+    pub const APP_NAME: &str = "qs-learn-box";
+    pub const SNAPSHOT_PROFILE: &str = "snapshot";
+    /// Saved once the tutorial has been finished or skipped, so it only shows on first launch.
+    pub const TUTORIAL_PROFILE: &str = "tutorial";
+    /// Snapshots older than this (in seconds) are ignored on launch.
+    pub const SNAPSHOT_MAX_AGE_SECS: u64 = 60 * 60;
 }
 
 pub mod graphics {
     pub const FONT_NAME: &str = "Georgia.ttf";
     pub const FONT_SIZE_PT: f32 = 18.0;
+    /// Size of the rixel labels drawn by the debug overlay.
+    #[cfg(feature = "debug")]
+    pub const DEBUG_FONT_SIZE_PT: f32 = 11.0;
+    /// Opacity of the hottest cell of the debug obstacle heatmap.
+    #[cfg(feature = "debug")]
+    pub const HEATMAP_MAX_ALPHA: f32 = 0.6;
 
     pub const HUD_CORNER_PADDING: f32 = 15.0;
+    /// How long the FPS readout stays red after a frame spike.
+    pub const FRAME_SPIKE_HIGHLIGHT_MS: u64 = 1000;
+
+    /// Layers the field is drawn in, bottom first. See `DrawCommand`.
+    pub const Z_FIELD: i32 = 0;
+    pub const Z_DANGER_MAP: i32 = 1;
+    pub const Z_PLAYER: i32 = 2;
+    pub const Z_OBSTACLES: i32 = 3;
 
     pub const FIELD_EDGE_BORDER_WIDTH: f32 = 1.0;
     pub const OBSTACLE_WARNING_WIDTH: f32 = 1.0;
+    /// How much thicker the warning line is for the fastest obstacles than the slowest.
+    pub const OBSTACLE_WARNING_URGENT_EXTRA_WIDTH: f32 = 2.0;
+    /// How much of an accelerating obstacle's front end is drawn brighter.
+    pub const OBSTACLE_LEADING_EDGE_LENGTH: f32 = 12.0;
+
+    pub const FIELD_GRID_SPACING: f32 = 50.0;
+    pub const FIELD_GRID_LINE_WIDTH: f32 = 1.0;
+    /// Opacity of the grid lines, drawn in the field border color.
+    pub const FIELD_GRID_ALPHA: f32 = 0.07;
+    /// The vignette is made of frames this wide, each fainter than the one outside it.
+    pub const VIGNETTE_BAND_WIDTH: f32 = 12.0;
+    pub const VIGNETTE_BANDS: u32 = 4;
+    /// The outermost vignette band pulses between these two opacities.
+    pub const VIGNETTE_MIN_ALPHA: f32 = 0.04;
+    pub const VIGNETTE_MAX_ALPHA: f32 = 0.12;
+    pub const VIGNETTE_PULSE_PERIOD_MS: u64 = 4000;
+
+    pub const SAFE_ZONE_CELL_SIZE: f32 = 10.0;
+    /// The safe zone indicator pulses between these two opacities.
+    pub const SAFE_ZONE_ALPHA: f32 = 0.08;
+    pub const SAFE_ZONE_MIN_ALPHA: f32 = 0.03;
+    pub const SAFE_ZONE_PULSE_PERIOD_MS: u64 = 1500;
+    /// Opacity of the black layer drawn behind overlays like the quit prompt.
+    pub const DIM_ALPHA: f32 = 0.6;
+
+    pub const DANGER_MAP_CELL_SIZE: f32 = 20.0;
+    pub const DANGER_MAP_ALPHA: f32 = 0.3;
+    /// A danger map cell is fully red once the player would be hit by this many obstacles there.
+    pub const DANGER_MAP_SATURATION: usize = 3;
+
+    pub const PLAYER_BORDER_WIDTH: f32 = 1.0;
+    /// How many of the player's past positions trail behind them.
+    pub const PLAYER_TRAIL_LENGTH: usize = 8;
+    /// How much smaller and fainter each step back along the trail is, as a fraction of the
+    /// player.
+    pub const PLAYER_TRAIL_ALPHA_STEP: f32 = 0.1;
+    /// Size of the marker drawn where the player is dragging to.
+    pub const TOUCH_TARGET_SIZE: f32 = 10.0;
+    pub const TOUCH_TARGET_ALPHA: f32 = 0.6;
+
+    pub const STAMINA_BAR_HEIGHT: f32 = 4.0;
+    /// Gap between the bottom of the player and the stamina bar.
+    pub const STAMINA_BAR_GAP: f32 = 4.0;
+    pub const RESET_BAR_WIDTH: f32 = 200.0;
+    pub const RESET_BAR_HEIGHT: f32 = 4.0;
+    /// Gap between the bottom of the field and the reset bar.
+    pub const RESET_BAR_GAP: f32 = 8.0;
+    pub const BOMB_FLASH_DURATION_MS: u64 = 150;
+    /// How long the collector flashes white after it starts grazing an obstacle.
+    pub const GRAZE_FLASH_DURATION_MS: u64 = 150;
+    /// Opacity of the collector while a magnet has it enlarged.
+    pub const MAGNET_COLLECTOR_ALPHA: f32 = 0.6;
+    /// The collector blinks over the last this many ms of a magnet, so it doesn't run out
+    /// without warning.
+    pub const MAGNET_EXPIRY_BLINK_MS: u64 = 2000;
+    /// How long each half of that blink lasts.
+    pub const MAGNET_EXPIRY_BLINK_RATE_MS: u64 = 150;
+    pub const MILESTONE_FLASH_DURATION_MS: u64 = 400;
+    /// Opacity of the milestone flash when it starts.
+    pub const MILESTONE_FLASH_ALPHA: f32 = 0.35;
+    pub const BOSS_STROBE_PERIOD_MS: u64 = 300;
+    /// Obstacles over the player strobe at this rate once they have been hit.
+    pub const HIT_STROBE_PERIOD_MS: u64 = 500;
+    /// How far apart in their strobe obstacles with consecutive ids are, so that overlapping
+    /// ones don't pulse together.
+    pub const HIT_STROBE_OFFSET_MS: u64 = 170;
+
+    /// How much of the gap to the real score the HUD's score closes each second. Big bonuses
+    /// count up over about half a second.
+    pub const SCORE_COUNT_RATE: f32 = 8.0;
+    pub const SCORE_POPUP_DURATION_MS: u64 = 1200;
+    /// How far a score popup floats up over its lifetime.
+    pub const SCORE_POPUP_RISE: f32 = 40.0;
+
+    pub const BORDER_RIPPLE_DURATION_MS: u64 = 300;
+    pub const BORDER_RIPPLE_THICKNESS: f32 = 3.0;
+    /// How much wider than its obstacle a ripple is when it starts and when it ends.
+    pub const BORDER_RIPPLE_START_GROWTH: f32 = 4.0;
+    pub const BORDER_RIPPLE_END_GROWTH: f32 = 20.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{difficulty::*, game::*};
+    use std::time::Duration;
+
+    #[test]
+    fn warning_move_speed_matches_the_field() {
+        // A 500 pixel field crossed in 20 ticks.
+        assert_eq!(warning_move_speed(), 25.);
+        assert_eq!(OBSTACLE_WARNING_MOVE_SPEED, warning_move_speed());
+    }
+
+    #[test]
+    fn spawn_interval_never_rises_or_drops_below_the_floor() {
+        for preset in &DifficultyPreset::ALL {
+            let settings = preset.settings();
+            let mut last = settings.spawn_interval(0);
+            for score in (0..=10_000_000).step_by(1_000) {
+                let interval = settings.spawn_interval(score);
+                assert!(interval <= last, "{} at score {}", preset.name(), score);
+                assert!(interval >= settings.min_spawn_interval);
+                last = interval;
+            }
+        }
+    }
+
+    #[test]
+    fn spawn_interval_falls_with_score_until_a_custom_floor() {
+        let floor = Duration::from_millis(1500);
+        for preset in &DifficultyPreset::ALL {
+            let settings = DifficultySettings {
+                min_spawn_interval: floor,
+                ..preset.settings()
+            };
+            // Scores below 100 all count as 100.
+            assert_eq!(settings.spawn_interval(0), settings.spawn_interval(100));
+            let mut score = 100;
+            while score < 1_000_000 {
+                let now = settings.spawn_interval(score);
+                let later = settings.spawn_interval(score * 2);
+                if later > floor {
+                    assert!(now > later, "{} at score {}", preset.name(), score);
+                }
+                assert!(later >= floor);
+                score *= 2;
+            }
+            assert_eq!(settings.spawn_interval(1_000_000), floor);
+        }
+    }
 }
@@ -0,0 +1,97 @@
+use quicksilver::geom::{Rectangle, Vector};
+use std::sync::OnceLock;
+
+use super::consts::game::{FIELD_HEIGHT, FIELD_WIDTH};
+use super::consts::system::{
+    HEIGHT_FLAG, MIN_WIN_HEIGHT, MIN_WIN_WIDTH, PLAYFIELD_MARGIN, WIDTH_FLAG, WIN_HEIGHT, WIN_WIDTH,
+};
+use super::error::{Error, Result};
+
+static DISPLAY: OnceLock<Display> = OnceLock::new();
+
+/// The size of the window, and how the playfield is fit into it.
+#[derive(Debug, Clone, Copy)]
+pub struct Display {
+    pub width: u32,
+    pub height: u32,
+    /// How much the playfield is scaled by to leave `PLAYFIELD_MARGIN` around it. Never more than
+    /// 1, so a big window just has more room around the field.
+    pub scale: f32,
+}
+
+impl Display {
+    pub fn new(width: u32, height: u32) -> Self {
+        let fit = |space: u32, field: f32| (space as f32 - PLAYFIELD_MARGIN * 2.) / field;
+        let scale = fit(width, FIELD_WIDTH)
+            .min(fit(height, FIELD_HEIGHT))
+            .min(1.);
+        Display {
+            width,
+            height,
+            scale,
+        }
+    }
+
+    /// The display asked for with `WIDTH_FLAG` and `HEIGHT_FLAG`. A flag that isn't given keeps
+    /// the default size.
+    pub fn from_args(args: &[String]) -> Result<Display> {
+        let width = parse_size(args, WIDTH_FLAG, WIN_WIDTH, MIN_WIN_WIDTH)?;
+        let height = parse_size(args, HEIGHT_FLAG, WIN_HEIGHT, MIN_WIN_HEIGHT)?;
+        Ok(Display::new(width, height))
+    }
+
+    /// The display the game is running on. Until one is installed, this is the default size.
+    pub fn current() -> &'static Display {
+        DISPLAY.get_or_init(|| Display::new(WIN_WIDTH, WIN_HEIGHT))
+    }
+
+    /// Make this the display for the rest of the game. Has to happen before the window opens, and
+    /// only the first call does anything.
+    pub fn install(self) {
+        let _ = DISPLAY.set(self);
+    }
+
+    pub fn size(&self) -> Vector {
+        Vector::new(self.width, self.height)
+    }
+
+    /// Move `rect` from playfield coordinates to the window's, scaling it with the field and
+    /// putting the field in the middle of the window.
+    pub fn map_playfield(&self, rect: &Rectangle) -> Rectangle {
+        let field = Vector::new(FIELD_WIDTH, FIELD_HEIGHT) * self.scale;
+        let offset = (self.size() - field) / 2.;
+        Rectangle::new(rect.pos * self.scale + offset, rect.size * self.scale)
+    }
+
+    /// The point on the playfield under `pos`, a point in the window. The reverse of
+    /// `map_playfield`.
+    pub fn playfield_point(&self, pos: Vector) -> Vector {
+        let field = Vector::new(FIELD_WIDTH, FIELD_HEIGHT) * self.scale;
+        let offset = (self.size() - field) / 2.;
+        (pos - offset) / self.scale
+    }
+}
+
+/// The number of pixels after `flag` in `args`, or `default` if the flag isn't there.
+fn parse_size(args: &[String], flag: &str, default: u32, min: u32) -> Result<u32> {
+    let i = match args.iter().position(|arg| arg == flag) {
+        Some(i) => i,
+        None => return Ok(default),
+    };
+    let value = args
+        .get(i + 1)
+        .ok_or_else(|| Error::InvalidArgument(format!("{} needs a size in pixels", flag)))?;
+    let size = value.parse::<u32>().map_err(|_| {
+        Error::InvalidArgument(format!(
+            "{} takes a size in pixels, not \"{}\"",
+            flag, value
+        ))
+    })?;
+    if size < min {
+        return Err(Error::InvalidArgument(format!(
+            "{} has to be at least {} pixels",
+            flag, min
+        )));
+    }
+    Ok(size)
+}
@@ -1,15 +1,37 @@
-use quicksilver;
+use quicksilver::{self, geom::Vector};
 
-use std::{fmt, result};
+use std::{fmt, fs::OpenOptions, io, io::Write, result};
+
+use super::consts::system::CRASH_LOG_PATH;
 
 #[derive(Debug)]
 pub enum Error {
     ObstacleRixelOutOfBounds(f32),
-    QuicksilverError(quicksilver::Error),
+    /// A point that was meant to be on the field's border isn't.
+    PixelOffBorder(Vector),
+    Quicksilver(quicksilver::Error),
+    Io(io::Error),
+    ConfigParse(toml::de::Error),
+    ConfigWrite(toml::ser::Error),
+    InvalidReplay(&'static str),
+    /// Capturing or saving a screenshot failed.
+    Screenshot(String),
+    /// A command line flag was given a value that can't be used.
+    InvalidArgument(String),
 }
 
 pub type Result<T> = result::Result<T, Error>;
 
+impl Error {
+    /// Whether this error is an I/O error caused by a missing file.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Error::Io(e) => e.kind() == io::ErrorKind::NotFound,
+            _ => false,
+        }
+    }
+}
+
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         None
@@ -22,14 +44,106 @@ impl fmt::Display for Error {
             Error::ObstacleRixelOutOfBounds(pos) => {
                 write!(f, "Obstacle position {} is out of bonds", pos)
             }
-            Error::QuicksilverError(err) => err.fmt(f),
+            Error::PixelOffBorder(pos) => write!(f, "Point {} is not on the field's border", pos),
+            Error::Quicksilver(err) => err.fmt(f),
+            Error::Io(err) => err.fmt(f),
+            Error::ConfigParse(err) => write!(f, "Invalid config: {}", err),
+            Error::ConfigWrite(err) => write!(f, "Could not write config: {}", err),
+            Error::InvalidReplay(reason) => write!(f, "Invalid replay: {}", reason),
+            Error::Screenshot(reason) => write!(f, "Screenshot failed: {}", reason),
+            Error::InvalidArgument(reason) => write!(f, "Invalid argument: {}", reason),
         }
     }
 }
 
+/// What the game was doing when an error stopped the run, for `CRASH_LOG_PATH`.
+pub struct CrashReport<'a> {
+    pub error: &'a Error,
+    /// Whether the game had to close, or could carry on to the error screen.
+    pub fatal: bool,
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub tick: u64,
+    pub recent_fps: Option<f64>,
+    pub obstacles: usize,
+}
+
+impl CrashReport<'_> {
+    /// Add the report to the end of `CRASH_LOG_PATH`, so earlier ones are kept.
+    pub fn write(&self) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(CRASH_LOG_PATH)?;
+        writeln!(file, "{}\n", self)
+    }
+}
+
+impl fmt::Display for CrashReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = if self.fatal { "Fatal error" } else { "Error" };
+        writeln!(f, "{} at {}: {}", kind, self.time, self.error)?;
+        writeln!(f, "  Tick: {}", self.tick)?;
+        match self.recent_fps {
+            Some(fps) => writeln!(f, "  Recent FPS: {:.1}", fps)?,
+            None => writeln!(f, "  Recent FPS: unknown")?,
+        }
+        write!(f, "  Obstacles: {}", self.obstacles)
+    }
+}
+
 impl From<quicksilver::Error> for Error {
     fn from(e: quicksilver::Error) -> Self {
-        Error::QuicksilverError(e)
+        Error::Quicksilver(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::ConfigParse(e)
     }
 }
 
+impl From<toml::ser::Error> for Error {
+    fn from(e: toml::ser::Error) -> Self {
+        Error::ConfigWrite(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crash_reports_say_what_the_game_was_doing() {
+        let error = Error::InvalidReplay("truncated");
+        let report = CrashReport {
+            error: &error,
+            fatal: false,
+            time: 1_700_000_000,
+            tick: 1234,
+            recent_fps: Some(59.94),
+            obstacles: 17,
+        };
+        let text = report.to_string();
+        assert!(text.starts_with("Error at 1700000000: "));
+        assert!(text.contains("  Tick: 1234\n"));
+        assert!(text.contains("  Recent FPS: 59.9\n"));
+        assert!(text.ends_with("  Obstacles: 17"));
+
+        let fatal = CrashReport {
+            fatal: true,
+            recent_fps: None,
+            ..report
+        };
+        let text = fatal.to_string();
+        assert!(text.starts_with("Fatal error at "));
+        assert!(text.contains("  Recent FPS: unknown\n"));
+    }
+}
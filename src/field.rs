@@ -0,0 +1,100 @@
+use quicksilver::geom::{Rectangle, Vector};
+
+use super::consts::game::{FIELD_HEIGHT, FIELD_WIDTH};
+
+/// The size of the playfield, and the measurements derived from it. Positions are relative to the
+/// field's upper-left corner.
+///
+/// The game's field is fixed at `FIELD_WIDTH` by `FIELD_HEIGHT`, the `Default`. Rixel
+/// coordinates, obstacle paths and the display scale are all built on those constants, so a game
+/// on a field of any other size would not line up with them. Other sizes are only for working
+/// out border geometry, such as `Direction::side_start_on`.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldGeometry {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl FieldGeometry {
+    pub fn new(width: f32, height: f32) -> Self {
+        FieldGeometry { width, height }
+    }
+
+    /// Length of the field's border, i.e. the number of rixels around it.
+    pub fn perimeter(&self) -> f32 {
+        (self.width + self.height) * 2.
+    }
+
+    pub fn center(&self) -> Vector {
+        Vector::new(self.width / 2., self.height / 2.)
+    }
+
+    /// The whole field as a rectangle.
+    pub fn rect(&self) -> Rectangle {
+        Rectangle::new((0., 0.), (self.width, self.height))
+    }
+
+    /// Lines `thickness` wide every `spacing` across and down the inside of the field, not counting
+    /// its edges.
+    pub fn grid_lines(&self, spacing: f32, thickness: f32) -> Vec<Rectangle> {
+        let mut lines = Vec::new();
+        let mut x = spacing;
+        while x < self.width {
+            lines.push(Rectangle::new((x - thickness / 2., 0.), (thickness, self.height)));
+            x += spacing;
+        }
+        let mut y = spacing;
+        while y < self.height {
+            lines.push(Rectangle::new((0., y - thickness / 2.), (self.width, thickness)));
+            y += spacing;
+        }
+        lines
+    }
+
+    /// A `thickness` wide frame running around the field `inset` in from its edges, as four
+    /// rectangles that don't overlap.
+    pub fn frame(&self, inset: f32, thickness: f32) -> [Rectangle; 4] {
+        let (w, h) = (self.width - inset * 2., self.height - inset * 2.);
+        [
+            Rectangle::new((inset, inset), (w, thickness)),
+            Rectangle::new((inset, inset + h - thickness), (w, thickness)),
+            Rectangle::new((inset, inset + thickness), (thickness, h - thickness * 2.)),
+            Rectangle::new(
+                (inset + w - thickness, inset + thickness),
+                (thickness, h - thickness * 2.),
+            ),
+        ]
+    }
+
+    /// Whether `rect` lies entirely inside the field.
+    pub fn contains(&self, rect: &Rectangle) -> bool {
+        rect.pos.x >= 0.
+            && rect.pos.y >= 0.
+            && rect.pos.x + rect.size.x <= self.width
+            && rect.pos.y + rect.size.y <= self.height
+    }
+
+    /// Move `rect` the shortest distance needed to put it back inside the field.
+    pub fn clamp_to_bounds(&self, rect: &mut Rectangle) {
+        if self.contains(rect) {
+            return;
+        }
+
+        if rect.pos.x + rect.size.x > self.width {
+            rect.pos.x = self.width - rect.size.x;
+        } else if rect.pos.x < 0. {
+            rect.pos.x = 0.;
+        }
+        if rect.pos.y + rect.size.y > self.height {
+            rect.pos.y = self.height - rect.size.y;
+        } else if rect.pos.y < 0. {
+            rect.pos.y = 0.;
+        }
+    }
+}
+
+impl Default for FieldGeometry {
+    fn default() -> Self {
+        FieldGeometry::new(FIELD_WIDTH, FIELD_HEIGHT)
+    }
+}
@@ -1,6 +1,15 @@
-use quicksilver::graphics::Color;
+use quicksilver::{
+    geom::{Rectangle, Shape, Vector},
+    graphics::{Background, Color, Font, FontStyle},
+    lifecycle::{Asset, Window},
+};
 use std::time::Duration;
 
+use super::consts::graphics::DIM_ALPHA;
+use super::display::Display;
+use super::error::Result;
+use super::util::{Clock, Lerp};
+
 fn color_to_u8(c: &Color) -> (u8, u8, u8) {
     let convert = |f: f32| (f * u8::max_value() as f32) as u8;
     (convert(c.r), convert(c.g), convert(c.b))
@@ -8,13 +17,34 @@ fn color_to_u8(c: &Color) -> (u8, u8, u8) {
 
 pub trait Strobe {
     fn strobe(&self, time: &Duration, rate: Duration) -> Color;
+    /// Like `strobe`, but oscillates the alpha between `self.a` and `min_alpha` and leaves the
+    /// color alone.
+    fn strobe_alpha(&self, time: &Duration, rate: Duration, min_alpha: f32) -> Color;
+
+    /// Blink between `self` and `other`, switching every `rate` with no blending in between.
+    fn checkerboard(&self, other: &Color, time: &Duration, rate: Duration) -> Color
+    where
+        Self: Copy + Into<Color>,
+    {
+        let rate = rate.as_millis().max(1);
+        if (time.as_millis() / rate).is_multiple_of(2) {
+            (*self).into()
+        } else {
+            *other
+        }
+    }
+}
+
+/// Where a strobe is in its cycle, from 1 at the start of each period down to 0 halfway through.
+fn strobe_blend(time: &Duration, rate: Duration) -> f32 {
+    let period = time.as_millis() as f32 / rate.as_millis() as f32;
+    (period * std::f32::consts::PI * 2.).cos() * 0.5 + 0.5
 }
 
 impl Strobe for Color {
     fn strobe(&self, time: &Duration, rate: Duration) -> Color {
         let color8 = color_to_u8(self);
-        let period = time.as_millis() as f32 / rate.as_millis() as f32;
-        let blend = |cdiff| (cdiff as f32 * ((period as f32 * std::f32::consts::PI * 2.).cos() * 0.5 + 0.5)) as u8;
+        let blend = |cdiff| (cdiff as f32 * strobe_blend(time, rate)) as u8;
         Color::from_rgba(
             color8.0 + blend(u8::max_value() - color8.0),
             color8.1 + blend(u8::max_value() - color8.1),
@@ -22,4 +52,292 @@ impl Strobe for Color {
             self.a
         )
     }
+
+    fn strobe_alpha(&self, time: &Duration, rate: Duration, min_alpha: f32) -> Color {
+        self.with_alpha(min_alpha.lerp(&self.a, strobe_blend(time, rate)))
+    }
+}
+
+/// Blend linearly from `from` at `t = 0` to `to` at `t = 1`.
+pub fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0., 1.);
+    let lerp = |a: f32, b: f32| a.lerp(&b, t);
+    Color {
+        r: lerp(from.r, to.r),
+        g: lerp(from.g, to.g),
+        b: lerp(from.b, to.b),
+        a: lerp(from.a, to.a),
+    }
+}
+
+/// A translucent overlay over the whole field that fades out, for marking events.
+pub struct ScreenFlash {
+    pub color: Color,
+    pub duration: Duration,
+    /// Game time the flash started at.
+    pub start: f64,
+}
+
+impl ScreenFlash {
+    pub fn new(clock: &impl Clock, color: Color, duration: Duration) -> Self {
+        ScreenFlash {
+            color,
+            duration,
+            start: clock.now(),
+        }
+    }
+
+    /// The flash color, faded out by how much of its duration has passed.
+    pub fn current_color(&self, clock: &impl Clock) -> Color {
+        let progress = clock.since(self.start).as_secs_f32() / self.duration.as_secs_f32();
+        self.color.with_alpha(self.color.a.lerp(&0., progress.min(1.)))
+    }
+
+    pub fn is_done(&self, clock: &impl Clock) -> bool {
+        clock.since(self.start) >= self.duration
+    }
+}
+
+/// The set of colors the game is drawn with.
+#[derive(Debug, Clone, Copy)]
+pub struct ColorScheme {
+    pub background: Color,
+    pub field_border: Color,
+    pub player: Color,
+    pub obstacle: Color,
+    pub fast_obstacle: Color,
+    pub wide_obstacle: Color,
+    pub bonus_obstacle: Color,
+    pub accelerating_obstacle: Color,
+    pub blocker_obstacle: Color,
+    /// Used for both gold obstacles and their warning lines.
+    pub gold_obstacle: Color,
+    pub collector: Color,
+    pub warning: Color,
+    /// What the player pulses toward when grazing.
+    pub highlight: Color,
+    pub hud: Color,
+}
+
+impl ColorScheme {
+    pub fn classic() -> Self {
+        ColorScheme {
+            background: Color::BLACK,
+            field_border: Color::WHITE,
+            player: Color::RED,
+            obstacle: Color::RED,
+            fast_obstacle: Color::ORANGE,
+            wide_obstacle: Color::from_rgba(180, 0, 0, 1.0),
+            bonus_obstacle: Color::from_rgba(255, 215, 0, 1.0),
+            accelerating_obstacle: Color::from_rgba(255, 60, 200, 1.0),
+            blocker_obstacle: Color::from_rgba(140, 40, 40, 1.0),
+            gold_obstacle: Color::from_rgba(218, 165, 32, 1.0),
+            collector: Color::BLUE,
+            warning: Color::WHITE,
+            highlight: Color::YELLOW,
+            hud: Color::WHITE,
+        }
+    }
+
+    pub fn neon() -> Self {
+        ColorScheme {
+            background: Color::from_rgba(12, 0, 24, 1.0),
+            field_border: Color::MAGENTA,
+            player: Color::from_rgba(0, 255, 200, 1.0),
+            obstacle: Color::from_rgba(255, 0, 150, 1.0),
+            fast_obstacle: Color::from_rgba(255, 90, 0, 1.0),
+            wide_obstacle: Color::from_rgba(170, 0, 110, 1.0),
+            bonus_obstacle: Color::from_rgba(255, 215, 0, 1.0),
+            accelerating_obstacle: Color::from_rgba(0, 200, 255, 1.0),
+            blocker_obstacle: Color::from_rgba(200, 80, 255, 1.0),
+            gold_obstacle: Color::from_rgba(255, 170, 0, 1.0),
+            collector: Color::from_rgba(120, 0, 255, 1.0),
+            warning: Color::CYAN,
+            highlight: Color::WHITE,
+            hud: Color::WHITE,
+        }
+    }
+
+    pub fn monochrome() -> Self {
+        ColorScheme {
+            background: Color::BLACK,
+            field_border: Color::WHITE,
+            player: Color::WHITE,
+            obstacle: Color::from_rgba(200, 200, 200, 1.0),
+            fast_obstacle: Color::WHITE,
+            wide_obstacle: Color::from_rgba(130, 130, 130, 1.0),
+            bonus_obstacle: Color::from_rgba(255, 215, 0, 1.0),
+            accelerating_obstacle: Color::from_rgba(230, 230, 230, 1.0),
+            blocker_obstacle: Color::from_rgba(100, 100, 100, 1.0),
+            gold_obstacle: Color::from_rgba(218, 165, 32, 1.0),
+            collector: Color::from_rgba(80, 80, 80, 1.0),
+            warning: Color::from_rgba(160, 160, 160, 1.0),
+            highlight: Color::from_rgba(120, 120, 120, 1.0),
+            hud: Color::WHITE,
+        }
+    }
+
+    /// Look up a built-in scheme by the name used in the config file.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Self::classic()),
+            "neon" => Some(Self::neon()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+}
+
+/// Player and collector colors drawn in place of the color scheme's own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skin {
+    pub player: Color,
+    pub collector: Color,
+}
+
+/// The skins F6 cycles through. The first leaves the color scheme's own colors alone.
+pub const SKINS: [Option<Skin>; 5] = [
+    None,
+    Some(Skin {
+        player: Color::GREEN,
+        collector: Color::PURPLE,
+    }),
+    Some(Skin {
+        player: Color::ORANGE,
+        collector: Color::CYAN,
+    }),
+    Some(Skin {
+        player: Color::MAGENTA,
+        collector: Color::YELLOW,
+    }),
+    Some(Skin {
+        player: Color::WHITE,
+        collector: Color::INDIGO,
+    }),
+];
+
+/// Draw `lines` as a block of text, each line centered horizontally on `center` and the whole block
+/// centered vertically on it.
+pub fn draw_text_lines(
+    window: &mut Window,
+    font: &mut Asset<Font>,
+    style: &FontStyle,
+    lines: &[String],
+    center: Vector,
+) -> Result<()> {
+    font.execute(|font| {
+        let imgs = lines
+            .iter()
+            .map(|line| font.render(line, style))
+            .collect::<quicksilver::Result<Vec<_>>>()?;
+        let total_height: f32 = imgs.iter().map(|img| img.area().height()).sum();
+        let mut y = center.y - total_height / 2.;
+        for img in &imgs {
+            let height = img.area().height();
+            window.draw(
+                &img.area().with_center((center.x, y + height / 2.)),
+                Background::Img(img),
+            );
+            y += height;
+        }
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// A colored rectangle waiting to be drawn. The field and everything on it is built up as a list of
+/// these without touching the window, then drawn in one go by `flush_draw_commands`.
+#[derive(Debug, Clone, Copy)]
+pub struct DrawCommand {
+    /// Where to draw, in window coordinates.
+    pub rect: Rectangle,
+    pub color: Color,
+    /// Commands with a higher `z` are drawn on top.
+    pub z: i32,
+}
+
+impl DrawCommand {
+    pub fn new(rect: Rectangle, color: Color, z: i32) -> Self {
+        DrawCommand { rect, color, z }
+    }
+}
+
+/// Draw `commands` from the lowest `z` up. Commands with the same `z` are drawn in the order they
+/// were made.
+pub fn flush_draw_commands(window: &mut Window, mut commands: Vec<DrawCommand>) {
+    commands.sort_by_key(|command| command.z);
+    for command in &commands {
+        window.draw(&command.rect, Background::Col(command.color));
+    }
+}
+
+/// An edge or corner of the window that HUD elements line up against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HudAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    TopCenter,
+}
+
+impl HudAnchor {
+    fn is_top(self) -> bool {
+        match self {
+            HudAnchor::TopLeft | HudAnchor::TopRight | HudAnchor::TopCenter => true,
+            HudAnchor::BottomLeft | HudAnchor::BottomRight => false,
+        }
+    }
+
+    /// Where to draw an element of `size` in a window of `screen` size, `padding` in from the
+    /// anchor and a further `offset` away from the top or bottom edge.
+    pub fn place(self, screen: Vector, padding: f32, size: Vector, offset: f32) -> Rectangle {
+        let x = match self {
+            HudAnchor::TopLeft | HudAnchor::BottomLeft => padding,
+            HudAnchor::TopRight | HudAnchor::BottomRight => screen.x - padding - size.x,
+            HudAnchor::TopCenter => (screen.x - size.x) / 2.,
+        };
+        let y = if self.is_top() {
+            padding + offset
+        } else {
+            screen.y - padding - offset - size.y
+        };
+        Rectangle::new((x, y), size)
+    }
+}
+
+/// Lays out a frame's HUD elements. Elements placed on the same anchor stack away from the edge
+/// it is on, in the order they are placed.
+pub struct HudStack {
+    screen: Vector,
+    padding: f32,
+    /// How far each anchor's stack already reaches, indexed by `HudAnchor as usize`.
+    used: [f32; 5],
+}
+
+impl HudStack {
+    pub fn new(screen: Vector, padding: f32) -> Self {
+        HudStack {
+            screen,
+            padding,
+            used: [0.; 5],
+        }
+    }
+
+    /// Make room for an element of `size` on `anchor` and return where to draw it.
+    pub fn place(&mut self, anchor: HudAnchor, size: Vector) -> Rectangle {
+        let rect = anchor.place(self.screen, self.padding, size, self.used[anchor as usize]);
+        self.used[anchor as usize] += size.y;
+        rect
+    }
+}
+
+/// Darken the whole window, so whatever is drawn next stands out from the game behind it.
+pub fn draw_dimmer(window: &mut Window) {
+    let size = Display::current().size();
+    window.draw(
+        &Rectangle::new((0., 0.), size),
+        Background::Col(Color::BLACK.with_alpha(DIM_ALPHA)),
+    );
 }
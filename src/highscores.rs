@@ -0,0 +1,197 @@
+use quicksilver::input::{ButtonState, Key, Keyboard};
+use serde::{Deserialize, Serialize};
+
+use std::{collections::BTreeMap, fs, io, time::Duration};
+
+use super::consts::difficulty::DifficultyPreset;
+use super::consts::system::{
+    HIGH_SCORES_PATH, HIGH_SCORES_VERSION, HIGH_SCORE_INITIALS_CHARS, HIGH_SCORE_TABLE_SIZE,
+};
+use super::error::Result;
+use super::stats::{format_clock, format_date};
+use super::util::{self, format_score};
+
+/// A finished run that made it onto the high score table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub score: u32,
+    /// When the run ended, in seconds since the Unix epoch.
+    pub achieved_at: u64,
+    pub run_duration: Duration,
+    pub difficulty: DifficultyPreset,
+    /// Three characters picked by the player once the run was over.
+    pub initials: String,
+}
+
+/// The best `HIGH_SCORE_TABLE_SIZE` runs, best first, kept in `HIGH_SCORES_PATH`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HighScoreTable {
+    /// `HIGH_SCORES_VERSION` when the file was written. Files from any other version are ignored.
+    version: u32,
+    entries: Vec<HighScoreEntry>,
+    /// The best score on each `Mode::Daily` seed played, by seed.
+    #[serde(default)]
+    daily_bests: BTreeMap<u64, u32>,
+}
+
+impl Default for HighScoreTable {
+    fn default() -> Self {
+        HighScoreTable {
+            version: HIGH_SCORES_VERSION,
+            entries: Vec::new(),
+            daily_bests: BTreeMap::new(),
+        }
+    }
+}
+
+impl HighScoreTable {
+    pub fn load() -> Result<HighScoreTable> {
+        let text = fs::read_to_string(HIGH_SCORES_PATH)?;
+        Ok(serde_json::from_str(&text).map_err(io::Error::from)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(HIGH_SCORES_PATH, text)?;
+        Ok(())
+    }
+
+    /// Load the table, starting an empty one if there is no file yet, or it can't be read, or it
+    /// was written by a different version of the game.
+    pub fn load_or_default() -> HighScoreTable {
+        match HighScoreTable::load() {
+            Ok(table) if table.version == HIGH_SCORES_VERSION => table,
+            Ok(table) => {
+                eprintln!(
+                    "Warning: ignoring {} from version {}, expected version {}",
+                    HIGH_SCORES_PATH, table.version, HIGH_SCORES_VERSION
+                );
+                HighScoreTable::default()
+            }
+            Err(e) => {
+                if !e.is_not_found() {
+                    eprintln!("Warning: could not load {}: {}", HIGH_SCORES_PATH, e);
+                }
+                HighScoreTable::default()
+            }
+        }
+    }
+
+    /// Whether a run scoring `score` would make it onto the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        self.entries.len() < HIGH_SCORE_TABLE_SIZE || self.entries.iter().any(|e| score > e.score)
+    }
+
+    /// Put `entry` in its place on the table, after any runs with the same score, dropping
+    /// whatever falls off the bottom.
+    pub fn insert(&mut self, entry: HighScoreEntry) {
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HIGH_SCORE_TABLE_SIZE);
+    }
+
+    pub fn daily_best(&self, seed: u64) -> Option<u32> {
+        self.daily_bests.get(&seed).copied()
+    }
+
+    /// Record a daily run's score against its seed. Returns whether it beat the seed's best.
+    pub fn record_daily(&mut self, seed: u64, score: u32) -> bool {
+        if score <= self.daily_best(seed).unwrap_or(0) {
+            return false;
+        }
+        self.daily_bests.insert(seed, score);
+        true
+    }
+
+    /// One line per entry with its rank, for the high score screen.
+    pub fn lines(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            return vec!["No runs yet".to_string()];
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                format!(
+                    "{}. {}  {}  {}  {}  {}",
+                    i + 1,
+                    e.initials,
+                    format_score(e.score),
+                    format_clock(e.run_duration),
+                    e.difficulty.name(),
+                    format_date(e.achieved_at)
+                )
+            })
+            .collect()
+    }
+}
+
+/// The player picking their initials for a run that made the high score table. Left and right
+/// pick a letter, up and down change it, and Enter confirms.
+pub struct InitialsEntry {
+    entry: HighScoreEntry,
+    initials: [char; 3],
+    /// Which of `initials` the arrow keys are changing.
+    slot: usize,
+}
+
+impl InitialsEntry {
+    pub fn new(entry: HighScoreEntry) -> Self {
+        InitialsEntry {
+            entry,
+            initials: ['A'; 3],
+            slot: 0,
+        }
+    }
+
+    /// Handle this tick's keys. Returns the finished entry once the player confirms.
+    pub fn update(&mut self, keyboard: &Keyboard) -> Option<HighScoreEntry> {
+        let pressed = |key: Key| keyboard[key] == ButtonState::Pressed;
+        let chars: Vec<char> = HIGH_SCORE_INITIALS_CHARS.chars().collect();
+        let slots = self.initials.len();
+
+        if pressed(Key::Left) {
+            self.slot = (self.slot + slots - 1) % slots;
+        } else if pressed(Key::Right) {
+            self.slot = (self.slot + 1) % slots;
+        }
+        let current = &mut self.initials[self.slot];
+        if pressed(Key::Up) {
+            *current = util::cycle(&chars, *current, 1);
+        } else if pressed(Key::Down) {
+            *current = util::cycle(&chars, *current, -1);
+        }
+
+        if pressed(Key::Return) {
+            let mut entry = self.entry.clone();
+            entry.initials = self.initials.iter().collect();
+            return Some(entry);
+        }
+        None
+    }
+
+    /// The prompt, with the letter being changed in brackets.
+    pub fn lines(&self) -> Vec<String> {
+        let initials: Vec<String> = self
+            .initials
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i == self.slot {
+                    format!("[{}]", c)
+                } else {
+                    format!(" {} ", c)
+                }
+            })
+            .collect();
+        vec![
+            "New high score! Enter your initials".to_string(),
+            initials.concat(),
+            "Arrows to change, Enter to confirm".to_string(),
+        ]
+    }
+}
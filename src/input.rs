@@ -0,0 +1,261 @@
+use quicksilver::{
+    geom::Vector,
+    input::{ButtonState, Gamepad, GamepadAxis, Key, Keyboard, Mouse, MouseButton},
+};
+use std::ops::BitOr;
+
+use super::consts::game::{GAMEPAD_STICK_DEAD_ZONE, TOUCH_DEAD_ZONE};
+use super::display::Display;
+use super::field::FieldGeometry;
+
+/// The player's input for one simulated tick.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct InputSnapshot {
+    pub left: bool,
+    pub down: bool,
+    pub up: bool,
+    pub right: bool,
+    pub slowmo: bool,
+    /// The bomb key was pressed this tick.
+    pub bomb: bool,
+}
+
+impl InputSnapshot {
+    /// Read this tick's input from the keyboard and the first gamepad. Stick tilt past the dead
+    /// zone counts the same as holding the matching key. Up is positive on the stick's Y axis.
+    pub fn from_keyboard(keyboard: &Keyboard, gamepad: Option<&Gamepad>) -> Self {
+        let stick = |axis| gamepad.map_or(0., |pad| pad[axis]);
+        let (stick_x, stick_y) = (
+            stick(GamepadAxis::LeftStickX),
+            stick(GamepadAxis::LeftStickY),
+        );
+        InputSnapshot {
+            left: keyboard[Key::H].is_down()
+                || keyboard[Key::Left].is_down()
+                || stick_x < -GAMEPAD_STICK_DEAD_ZONE,
+            down: keyboard[Key::J].is_down()
+                || keyboard[Key::Down].is_down()
+                || stick_y < -GAMEPAD_STICK_DEAD_ZONE,
+            up: keyboard[Key::K].is_down()
+                || keyboard[Key::Up].is_down()
+                || stick_y > GAMEPAD_STICK_DEAD_ZONE,
+            right: keyboard[Key::L].is_down()
+                || keyboard[Key::Right].is_down()
+                || stick_x > GAMEPAD_STICK_DEAD_ZONE,
+            slowmo: keyboard[Key::LShift].is_down(),
+            bomb: keyboard[Key::X] == ButtonState::Pressed,
+        }
+    }
+
+    /// Head from `player`, the center of the player, towards `target`, both in playfield
+    /// coordinates. Movement goes one way at a time, so this takes whichever axis is further off
+    /// and stops within `TOUCH_DEAD_ZONE` of the target. `slowmo` comes from a second button.
+    pub fn from_touch(target: Vector, player: Vector, slowmo: bool) -> Self {
+        let offset = target - player;
+        let horizontal = offset.x.abs() >= offset.y.abs();
+        let along = if horizontal { offset.x } else { offset.y };
+        let moving = along.abs() > TOUCH_DEAD_ZONE;
+        InputSnapshot {
+            left: moving && horizontal && along < 0.,
+            down: moving && !horizontal && along > 0.,
+            up: moving && !horizontal && along < 0.,
+            right: moving && horizontal && along > 0.,
+            slowmo,
+            bomb: false,
+        }
+    }
+}
+
+/// Holding either input counts, so the keyboard and touch can be used together.
+impl BitOr for InputSnapshot {
+    type Output = InputSnapshot;
+
+    fn bitor(self, other: InputSnapshot) -> InputSnapshot {
+        InputSnapshot {
+            left: self.left || other.left,
+            down: self.down || other.down,
+            up: self.up || other.up,
+            right: self.right || other.right,
+            slowmo: self.slowmo || other.slowmo,
+            bomb: self.bomb || other.bomb,
+        }
+    }
+}
+
+/// Where the player is dragging to, in playfield coordinates and kept inside `field`, or `None`
+/// if the left button or a touch isn't held.
+pub fn touch_target(mouse: &Mouse, field: &FieldGeometry) -> Option<Vector> {
+    if !mouse[MouseButton::Left].is_down() {
+        return None;
+    }
+    let pos = Display::current().playfield_point(mouse.pos());
+    Some(Vector::new(
+        pos.x.clamp(0., field.width),
+        pos.y.clamp(0., field.height),
+    ))
+}
+
+/// Whether slow-mo is being held with the mouse.
+pub fn touch_slowmo(mouse: &Mouse) -> bool {
+    mouse[MouseButton::Right].is_down()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::game::{
+        PLAYER_ACCELERATION, PLAYER_FRICTION, PLAYER_SLOWMO_FACTOR, PLAYER_SPEED,
+    };
+    use crate::util::Countdown;
+    use crate::GameState;
+    use std::time::Duration;
+
+    /// Allowed float error when comparing velocities.
+    const EPSILON: f32 = 1e-4;
+
+    /// A headless game with the player at rest in the middle of the field.
+    fn game() -> GameState {
+        let mut state = GameState::new_headless(86);
+        state.player.rect.pos = state.field.center();
+        state
+    }
+
+    /// Feed `input` to `state` for `ticks` ticks and return the player's velocity.
+    fn hold(state: &mut GameState, input: InputSnapshot, ticks: u32) -> Vector {
+        for _ in 0..ticks {
+            state.update_handle_input(input).unwrap();
+        }
+        state.player.velocity
+    }
+
+    #[test]
+    fn holding_a_direction_accelerates_that_way() {
+        let step = PLAYER_ACCELERATION * PLAYER_FRICTION;
+        let only = |left, down, up, right| InputSnapshot {
+            left,
+            down,
+            up,
+            right,
+            ..Default::default()
+        };
+        let cases = [
+            (only(true, false, false, false), Vector::new(-step, 0.)),
+            (only(false, true, false, false), Vector::new(0., step)),
+            (only(false, false, true, false), Vector::new(0., -step)),
+            (only(false, false, false, true), Vector::new(step, 0.)),
+        ];
+        for &(input, expected) in &cases {
+            let velocity = hold(&mut game(), input, 1);
+            assert!((velocity - expected).len() < EPSILON, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn only_one_direction_counts_at_a_time() {
+        let all = InputSnapshot {
+            left: true,
+            down: true,
+            up: true,
+            right: true,
+            ..Default::default()
+        };
+        let velocity = hold(&mut game(), all, 1);
+        assert!(velocity.x < 0.);
+        assert_eq!(velocity.y, 0.);
+    }
+
+    #[test]
+    fn speed_is_capped_lower_in_slowmo() {
+        let right = InputSnapshot {
+            right: true,
+            ..Default::default()
+        };
+        let velocity = hold(&mut game(), right, 30);
+        assert!((velocity.len() - PLAYER_SPEED).abs() < EPSILON);
+
+        let mut state = game();
+        let slowmo = InputSnapshot {
+            slowmo: true,
+            ..right
+        };
+        let velocity = hold(&mut state, slowmo, 30);
+        assert!((velocity.len() - PLAYER_SPEED / PLAYER_SLOWMO_FACTOR).abs() < EPSILON);
+        assert!(state.is_slowmo);
+        assert!(state.player.stamina < 1.);
+    }
+
+    #[test]
+    fn letting_go_coasts_to_a_stop() {
+        let mut state = game();
+        let right = InputSnapshot {
+            right: true,
+            ..Default::default()
+        };
+        hold(&mut state, right, 30);
+        let coasting = hold(&mut state, InputSnapshot::default(), 1);
+        assert!((coasting.x - PLAYER_SPEED * PLAYER_FRICTION).abs() < EPSILON);
+        let stopped = hold(&mut state, InputSnapshot::default(), 100);
+        assert!(stopped.len() < EPSILON);
+    }
+
+    #[test]
+    fn input_is_ignored_once_the_player_is_hit() {
+        let mut state = game();
+        state.reset_countdown = Some(Countdown::new(&state.clock, Duration::from_secs(2)));
+        let input = InputSnapshot {
+            left: true,
+            slowmo: true,
+            ..Default::default()
+        };
+        assert_eq!(hold(&mut state, input, 3), Vector::ZERO);
+        assert!(!state.is_slowmo);
+    }
+
+    #[test]
+    fn touch_heads_along_the_longer_axis() {
+        let player = Vector::new(100., 100.);
+        let touch = |x, y| InputSnapshot::from_touch(Vector::new(x, y), player, false);
+        assert_eq!(
+            touch(200., 130.),
+            InputSnapshot {
+                right: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            touch(80., 20.),
+            InputSnapshot {
+                up: true,
+                ..Default::default()
+            }
+        );
+        // Close enough to the target to stop.
+        assert_eq!(touch(105., 95.), InputSnapshot::default());
+        assert!(InputSnapshot::from_touch(player, player, true).slowmo);
+    }
+
+    #[test]
+    fn combined_inputs_hold_everything_either_holds() {
+        let keys = InputSnapshot {
+            left: true,
+            bomb: true,
+            ..Default::default()
+        };
+        let touch = InputSnapshot {
+            up: true,
+            slowmo: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            keys | touch,
+            InputSnapshot {
+                left: true,
+                up: true,
+                slowmo: true,
+                bomb: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(keys | InputSnapshot::default(), keys);
+    }
+}
@@ -1,31 +1,64 @@
 extern crate quicksilver;
 extern crate rand;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod bench;
+mod config;
 mod consts;
+mod display;
 mod error;
+mod field;
 mod graphics;
+mod highscores;
+mod input;
+mod replay;
+#[cfg(not(target_arch = "wasm32"))]
+mod screenshot;
+mod stats;
+mod tutorial;
 mod util;
 
 use quicksilver::{
+    combinators,
     geom::{Rectangle, Shape, Vector},
     graphics::{Background, Color, Font, FontStyle},
-    input::{Key, Keyboard},
-    lifecycle::{run, Asset, Settings, State, Window},
+    input::{ButtonState, GamepadButton, Key, Keyboard},
+    lifecycle::{run, Asset, Event, Settings, State, Window},
+    saving,
 };
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::{distributions::WeightedIndex, rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use std::{
-    cmp, time::{Duration, Instant},
-};
+use std::{cmp::Ordering, collections::VecDeque, convert::TryFrom, fmt, fs, io, time::Duration};
 
-use consts::{game::*, graphics::*, system::*};
-use error::{Error, Result};
-use graphics::Strobe;
-use util::{Countdown, FpsGraph};
+use config::Config;
+use consts::{
+    difficulty::{DifficultyPreset, DifficultySettings, ADAPTIVE_SIDE_BIAS},
+    game::*,
+    graphics::*,
+    system::*,
+};
+use display::Display;
+use error::{CrashReport, Error, Result};
+use field::FieldGeometry;
+use graphics::{
+    draw_dimmer, draw_text_lines, flush_draw_commands, lerp_color, ColorScheme, DrawCommand,
+    HudAnchor, HudStack, ScreenFlash, Skin, Strobe, SKINS,
+};
+use highscores::{HighScoreEntry, HighScoreTable, InitialsEntry};
+use input::{touch_slowmo, touch_target, InputSnapshot};
+use replay::{Replay, ReplayPlayback, ReplayRecorder};
+use stats::{format_clock, format_date, RunStats, SessionStats};
+use tutorial::Tutorial;
+use util::{
+    format_score, Clock, Countdown, DiagnosticsBuffer, EventLog, FpsGraph, FrameDiagnostics,
+    GameClock, Lerp, RollingMax, SpatialGrid, Stopwatch,
+};
 use core::borrow::Borrow;
 
-#[derive(Debug)]
+/// A side of the field.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum Direction {
     North,
     East,
@@ -33,501 +66,4141 @@ enum Direction {
     West,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Obstacle {
-    /// A measurement of where the obstacle is coming from. 1 rixel = 1 pixel around the perimeter
-    /// of the playfield, starting at the upper-left corner.
-    rixel: f32,
-    speed: f32,
-    width: f32,
-    length: f32,
-    lifetime: f32,
-}
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
 
-impl Obstacle {
-    /// Randomly generate a new obstacle.
-    fn spawn(rng: &mut ThreadRng) -> Obstacle {
-        let width = rng.gen_range(6.0, 14.0);
-        let rixel = FIELD_EDGE_LENGTH * rng.gen_range(0, 4) as f32;
-        let rixel = rixel + rng.gen_range(width / 2., FIELD_EDGE_LENGTH - width / 2.);
-        Obstacle {
-            rixel: rixel,
-            speed: 3.0,
-            width: width,
-            length: 300.0,
-            lifetime: -(OBSTACLE_PRE_SPAWN_WARN_TIME as f32),
+    /// The rixel this side of the game's field starts at. Rixels, and so obstacles, are laid out
+    /// on the default field; see `FieldGeometry`.
+    fn side_start(self) -> f32 {
+        self.side_start_on(&FieldGeometry::default())
+    }
+
+    /// The rixel this side of `field` starts at.
+    fn side_start_on(self, field: &FieldGeometry) -> f32 {
+        match self {
+            Direction::North => 0.,
+            Direction::East => field.width,
+            Direction::South => field.width + field.height,
+            Direction::West => field.width * 2. + field.height,
         }
     }
 
-    /// Calculates the distance in rixels from the given rixel to the next corner.
-    fn rixels_to_next_corner(rixel: f32) -> f32 {
-        FIELD_EDGE_LENGTH - (rixel % FIELD_EDGE_LENGTH)
+    /// Length of this side of the field in rixels.
+    fn side_length(self) -> f32 {
+        self.side_length_on(&FieldGeometry::default())
     }
 
-    /// Convert a numerical position (in rixels) to a side of the screen.
-    fn rixel_to_direction(rixel: f32) -> Result<Direction> {
-        if rixel > 0. && rixel < FIELD_EDGE_LENGTH {
-            Ok(Direction::North)
-        } else if rixel < FIELD_EDGE_LENGTH * 2. {
-            Ok(Direction::East)
-        } else if rixel < FIELD_EDGE_LENGTH * 3. {
-            Ok(Direction::South)
-        } else if rixel < FIELD_EDGE_LENGTH * 4. {
-            Ok(Direction::West)
-        } else {
-            Err(Error::ObstacleRixelOutOfBounds(rixel))
+    /// Length of this side of `field` in rixels.
+    fn side_length_on(self, field: &FieldGeometry) -> f32 {
+        match self.axis() {
+            Axis::Vertical => field.width,
+            Axis::Horizontal => field.height,
         }
     }
 
-    /// Convert obstacle positioning data (rixel, distance from edge, and dimensions) to a
-    /// rectangle.
-    fn positioning_to_rectangle(
-        rixel: f32,
-        distance: f32,
-        length: f32,
-        width: f32,
-    ) -> Result<Rectangle> {
-        use Direction::*;
-
-        let dir = Self::rixel_to_direction(rixel)?;
-        let distance_back = FIELD_EDGE_LENGTH - Self::rixels_to_next_corner(rixel);
-        Ok(Rectangle::new(
-            // Position
-            match dir {
-                North => (rixel - width / 2., -length + distance),
-                East => (
-                    FIELD_EDGE_LENGTH - distance,
-                    rixel - FIELD_EDGE_LENGTH - width / 2.,
-                ),
-                South => (
-                    rixel - distance_back * 2. - FIELD_EDGE_LENGTH - width / 2.,
-                    FIELD_EDGE_LENGTH - distance,
-                ),
-                West => (
-                    -length + distance,
-                    FIELD_EDGE_LENGTH * 4. - rixel - width / 2.,
-                ),
-            },
-            // Dimensions
-            match dir {
-                North | South => (width, length),
-                East | West => (length, width),
-            },
-        ))
+    /// The corner of the field this side's rixels start from.
+    fn start_corner(self) -> Vector {
+        self.start_corner_on(&FieldGeometry::default())
     }
 
-    /// Get this obstacle's rectangle.
-    fn rectangle(&self) -> Rectangle {
-        let distance = if self.lifetime * self.speed > FIELD_EDGE_LENGTH {
-            FIELD_EDGE_LENGTH
-        } else {
-            self.lifetime * self.speed
-        };
+    /// The corner of `field` this side's rixels start from.
+    fn start_corner_on(self, field: &FieldGeometry) -> Vector {
+        match self {
+            Direction::North => Vector::new(0., 0.),
+            Direction::East => Vector::new(field.width, 0.),
+            Direction::South => Vector::new(field.width, field.height),
+            Direction::West => Vector::new(0., field.height),
+        }
+    }
 
-        let length = if self.lifetime < 0. || self.lifetime > self.total_lifetime() {
-            0.
-        } else if self.lifetime * self.speed < self.length {
-            self.lifetime * self.speed
-        } else if self.lifetime * self.speed > FIELD_EDGE_LENGTH {
-            self.length - (self.lifetime * self.speed - FIELD_EDGE_LENGTH)
-        } else {
-            self.length
-        };
+    /// The side of `field` a rixel is on, or `None` if it is off the end of the border. Each side
+    /// covers the rixels from where it starts up to where the next one starts.
+    fn of_rixel(rixel: RixelCoord, field: &FieldGeometry) -> Option<Direction> {
+        if !(0. ..field.perimeter()).contains(&rixel.0) {
+            return None;
+        }
+        Self::ALL
+            .iter()
+            .cloned()
+            .find(|&side| rixel.0 < side.side_start_on(field) + side.side_length_on(field))
+    }
 
-        Obstacle::positioning_to_rectangle(self.rixel, distance, length, self.width).unwrap()
+    /// Which way something coming in from this side moves.
+    fn axis(self) -> Axis {
+        match self {
+            Direction::North | Direction::South => Axis::Vertical,
+            Direction::East | Direction::West => Axis::Horizontal,
+        }
     }
 
-    /// Get the rixel on the opposite side of the perimeter.
-    fn opposite(&self) -> f32 {
-        let to_next_corner = FIELD_EDGE_LENGTH - (self.rixel % FIELD_EDGE_LENGTH);
-        (self.rixel + to_next_corner + FIELD_EDGE_LENGTH + to_next_corner)
-            % (FIELD_EDGE_LENGTH * 4.)
+    /// Points out of the field through this side.
+    fn unit_vector(self) -> Vector {
+        match self {
+            Direction::North => Vector::new(0., -1.),
+            Direction::East => Vector::new(1., 0.),
+            Direction::South => Vector::new(0., 1.),
+            Direction::West => Vector::new(-1., 0.),
+        }
     }
 
-    /// The lifetime value at which this obstacle has moved completely offscreen.
-    fn total_lifetime(&self) -> f32 {
-        (FIELD_EDGE_LENGTH + self.length) / self.speed
+    /// Points along this side in the direction its rixels increase, i.e. clockwise.
+    fn clockwise_vector(self) -> Vector {
+        let out = self.unit_vector();
+        Vector::new(-out.y, out.x)
     }
-}
 
-/// Tracks information about the player and their avatar.
-#[derive(Debug)]
-struct Player {
-    rect: Rectangle,
-    score: u32,
-    color: Color,
-}
+    fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
 
-impl Player {
-    fn new() -> Player {
-        Player {
-            rect: Rectangle::new((0, 0), (50, 50)),
-            score: 0,
-            color: Color::RED,
+    /// Pick a side at random, weighted by `weights` in `ALL` order.
+    fn random(rng: &mut StdRng, weights: &[f32; 4]) -> Direction {
+        let dist = WeightedIndex::new(weights).unwrap();
+        Self::ALL[rng.sample(dist)]
+    }
+
+    /// The side of the field `rect` is closest to.
+    fn nearest(rect: &Rectangle, field: &FieldGeometry) -> Direction {
+        let distances = [
+            rect.y(),
+            field.width - (rect.x() + rect.width()),
+            field.height - (rect.y() + rect.height()),
+            rect.x(),
+        ];
+        let mut nearest = 0;
+        for (i, d) in distances.iter().enumerate() {
+            if *d < distances[nearest] {
+                nearest = i;
+            }
         }
+        Self::ALL[nearest]
     }
 
-    fn collector_rectangle(&self) -> Rectangle {
-        Rectangle::new_sized((COLLECTOR_EDGE_LENGTH, COLLECTOR_EDGE_LENGTH))
-            .with_center(self.rect.center())
+    /// How far something coming in from this side travels to reach the other side.
+    fn crossing_distance(self) -> f32 {
+        self.crossing_distance_on(&FieldGeometry::default())
+    }
+
+    /// How far something coming in from this side of `field` travels to reach the other side.
+    fn crossing_distance_on(self, field: &FieldGeometry) -> f32 {
+        match self.axis() {
+            Axis::Vertical => field.height,
+            Axis::Horizontal => field.width,
+        }
     }
 }
 
-struct GameState {
-    obstacles: Vec<Obstacle>,
-    player: Player,
-    rng: ThreadRng,
+/// The rixel a side of the field starts at.
+impl From<Direction> for RixelCoord {
+    fn from(side: Direction) -> RixelCoord {
+        RixelCoord(side.side_start())
+    }
+}
 
-    last_spawned: Option<Instant>,
-    spawn_interval: Duration,
+/// The rixel a fraction `t` of the way around the border is, the inverse of
+/// `Obstacle::rixel_normalized`.
+fn from_normalized(t: f32) -> RixelCoord {
+    RixelCoord(t * FIELD_PERIMETER)
+}
 
-    is_running: bool,
-    reset_countdown: Option<Countdown>,
+/// A position around the border of the field, in rixels. See `Obstacle`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+struct RixelCoord(pub f32);
 
-    fps_graph: FpsGraph,
-    fps_update_time: Option<Instant>,
+/// A point on the playfield, in pixels from its upper-left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PixelCoord(pub Vector);
 
-    font: Asset<Font>,
-    font_style: FontStyle,
+impl RixelCoord {
+    /// How far along `side` this rixel is from where the side starts.
+    fn along(self, side: Direction) -> f32 {
+        self.0 - side.side_start()
+    }
 }
 
-impl GameState {
-    /// Given the player's current score value, decide how long the wait for the next obstacle to
-    /// spawn should be.
-    fn obstacle_spawn_interval(score: u32) -> Duration {
-        let score = cmp::max(100, score);
-        let spawntime = ((SPAWN_RATE_FACTOR / (score as f32 / 100.).powf(1. / 3.)
-            - SPAWN_RATE_SUBTRACT)
-            * 1000.) as u64;
-        Duration::from_millis(spawntime)
+/// Where the rixel is on the field's border. Rixels wrap around, so anything past the end of
+/// West is back on North.
+impl From<RixelCoord> for PixelCoord {
+    fn from(rixel: RixelCoord) -> PixelCoord {
+        let rixel = RixelCoord(rixel.0.rem_euclid(FIELD_PERIMETER));
+        // Rounding can leave it right on the perimeter, which is the end of West.
+        let side = Obstacle::rixel_to_direction(rixel).unwrap_or(Direction::West);
+        PixelCoord(side.start_corner() + side.clockwise_vector() * rixel.along(side))
     }
 }
 
-// Drawing logic.
-impl GameState {
-    fn draw_obstacles(&self, window: &mut Window) -> Result<()> {
-        // Draw the obstacle warnings.
-        for obstacle in &self.obstacles {
-            // Didn't realize Quicksilver had a Line type lol.
-            let line_rect = if obstacle.lifetime < 0. {
-                let dist = FIELD_EDGE_LENGTH.min(
-                    OBSTACLE_WARNING_MOVE_SPEED
-                        * (obstacle.lifetime + OBSTACLE_PRE_SPAWN_WARN_TIME as f32),
-                );
-                Obstacle::positioning_to_rectangle(
-                    obstacle.rixel,
-                    dist,
-                    dist,
-                    OBSTACLE_WARNING_WIDTH,
-                )
-            } else if obstacle.lifetime - obstacle.total_lifetime() < OBSTACLE_HIDE_DELAY as f32 {
-                Obstacle::positioning_to_rectangle(
-                    obstacle.rixel,
-                    FIELD_EDGE_LENGTH,
-                    FIELD_EDGE_LENGTH,
-                    OBSTACLE_WARNING_WIDTH,
-                )
-            } else {
-                let dist = FIELD_EDGE_LENGTH
-                    - ((obstacle.lifetime
-                        - OBSTACLE_HIDE_DELAY as f32
-                        - obstacle.total_lifetime())
-                        * OBSTACLE_WARNING_MOVE_SPEED)
-                        .max(0.);
-                Obstacle::positioning_to_rectangle(
-                    obstacle.opposite(),
-                    dist,
-                    dist,
-                    OBSTACLE_WARNING_WIDTH,
-                )
-            }?;
+/// The rixel at a point on the field's border. Points anywhere else have no rixel.
+impl TryFrom<PixelCoord> for RixelCoord {
+    type Error = Error;
 
-            window.draw(&line_rect.on_playfield(), Background::Col(Color::WHITE));
-        }
+    fn try_from(pixel: PixelCoord) -> Result<RixelCoord> {
+        let PixelCoord(pos) = pixel;
+        Direction::ALL
+            .iter()
+            .find_map(|&side| {
+                let offset = pos - side.start_corner();
+                let along = offset.dot(side.clockwise_vector());
+                let on_side = offset.dot(side.unit_vector()) == 0.
+                    && along >= 0.
+                    && along < side.side_length();
+                Some(RixelCoord(side.side_start() + along)).filter(|_| on_side)
+            })
+            .ok_or(Error::PixelOffBorder(pos))
+    }
+}
 
-        // Then draw the obstacles themselves.
-        for obstacle in &self.obstacles {
-            let color = if obstacle.rectangle().overlaps_rectangle(&self.player.rect) && self.reset_countdown.is_some() {
-                let countdown = self.reset_countdown.as_ref().unwrap().elapsed();
-                Color::RED.strobe(&countdown, Duration::from_millis(500))
-            } else {
-                Color::RED
-            };
-            window.draw(
-                &obstacle.rectangle().on_playfield(),
-                Background::Col(color)
-            );
-        }
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
 
-        Ok(())
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum ObstacleKind {
+    Normal,
+    /// Twice as fast and half as long.
+    Fast,
+    /// Three times as wide, but slower.
+    Wide,
+    /// Worth extra points when it despawns, and never kills the player.
+    Bonus,
+    /// Enters at half speed and speeds up the whole way across.
+    Accelerating,
+    /// Stops in the middle of the field for a while before carrying on.
+    Blocker,
+    /// Kills like a normal obstacle, but pays out more for despawning and grazing. Its warning
+    /// line is drawn in gold so the player can plan to graze it.
+    Gold,
+    /// Covers half a side and crawls across. Only comes in at score milestones, never at random.
+    Boss,
+}
 
-    fn draw_field_border(&self, window: &mut Window) -> Result<()> {
-        window.draw(
-            &Rectangle::new(
-                (-FIELD_EDGE_BORDER_WIDTH, -FIELD_EDGE_BORDER_WIDTH),
-                (
-                    FIELD_EDGE_BORDER_WIDTH * 2. + FIELD_EDGE_LENGTH,
-                    FIELD_EDGE_BORDER_WIDTH * 2. + FIELD_EDGE_LENGTH,
-                ),
-            )
-            .on_playfield(),
-            Background::Col(Color::WHITE),
-        );
+impl ObstacleKind {
+    const ALL: [ObstacleKind; 8] = [
+        ObstacleKind::Normal,
+        ObstacleKind::Fast,
+        ObstacleKind::Wide,
+        ObstacleKind::Bonus,
+        ObstacleKind::Accelerating,
+        ObstacleKind::Blocker,
+        ObstacleKind::Gold,
+        ObstacleKind::Boss,
+    ];
 
-        window.draw(
-            &Rectangle::new((0, 0), (FIELD_EDGE_LENGTH, FIELD_EDGE_LENGTH)).on_playfield(),
-            Background::Col(Color::BLACK),
-        );
+    /// Pick a kind at random, with the rarer kinds growing more likely as the score goes up.
+    fn random(rng: &mut StdRng, score: u32) -> ObstacleKind {
+        let weights = (0..Self::ALL.len()).map(|i| {
+            OBSTACLE_KIND_WEIGHTS_BASE[i]
+                + OBSTACLE_KIND_WEIGHTS_PER_1000_SCORE[i] * score as f32 / 1000.
+        });
+        let dist = WeightedIndex::new(weights).unwrap();
+        Self::ALL[rng.sample(dist)]
+    }
 
-        Ok(())
+    fn is_lethal(self) -> bool {
+        self != ObstacleKind::Bonus
     }
 
-    fn draw_hud(&mut self, window: &mut Window) -> Result<()> {
-        let style = &self.font_style;
-        if let Some(fps) = &self.fps_graph.recent_average_fps() {
-            self.font.execute(|font| {
-                let img = font.render(&format!("{:.0}", fps), style)?;
-                window.draw(
-                    &Rectangle::new((HUD_CORNER_PADDING, HUD_CORNER_PADDING), img.area().size()),
-                    Background::Img(&img),
-                );
-                Ok(())
-            })?;
+    fn despawn_points(self) -> u32 {
+        match self {
+            ObstacleKind::Bonus => OBSTACLE_DESPAWN_POINTS * BONUS_OBSTACLE_POINTS_FACTOR,
+            ObstacleKind::Boss => BOSS_SURVIVAL_POINTS,
+            ObstacleKind::Gold => GOLD_OBSTACLE_DESPAWN_POINTS,
+            _ => OBSTACLE_DESPAWN_POINTS,
         }
+    }
 
-        let score = &self.player.score;
-        self.font.execute(|font| {
-            let img = font.render(&format!("{:09}", score), style)?;
-            window.draw(
-                &Rectangle::new(
-                    (WIN_WIDTH as f32 - img.area().width() - HUD_CORNER_PADDING, HUD_CORNER_PADDING),
-                    img.area().size(),
-                ),
-                Background::Img(&img),
-            );
-            Ok(())
-        })?;
-
-        Ok(())
+    /// Points for each tick the collector spends over the obstacle.
+    fn graze_points(self) -> u32 {
+        match self {
+            ObstacleKind::Gold => GOLD_OBSTACLE_GRAZE_POINTS,
+            _ => 1,
+        }
     }
 
-    fn draw_player(&mut self, window: &mut Window) -> Result<()> {
-        window.draw(
-            &self.player.collector_rectangle().on_playfield(),
-            Background::Col(Color::BLUE),
-        );
-        window.draw(
-            &self.player.rect.on_playfield(),
-            Background::Col(self.player.color),
-        );
+    fn color(self, scheme: &ColorScheme) -> Color {
+        match self {
+            ObstacleKind::Normal | ObstacleKind::Boss => scheme.obstacle,
+            ObstacleKind::Fast => scheme.fast_obstacle,
+            ObstacleKind::Wide => scheme.wide_obstacle,
+            ObstacleKind::Bonus => scheme.bonus_obstacle,
+            ObstacleKind::Accelerating => scheme.accelerating_obstacle,
+            ObstacleKind::Blocker => scheme.blocker_obstacle,
+            ObstacleKind::Gold => scheme.gold_obstacle,
+        }
+    }
 
-        Ok(())
+    fn warning_color(self, scheme: &ColorScheme) -> Color {
+        match self {
+            ObstacleKind::Gold => scheme.gold_obstacle,
+            _ => scheme.warning,
+        }
     }
 }
 
-// Update logic
-impl GameState {
-    fn update_handle_input(&mut self, keyboard: &Keyboard) -> quicksilver::Result<()> {
-        let movespeed = if keyboard[Key::LShift].is_down() {
-            PLAYER_SPEED / PLAYER_SLOWMO_FACTOR
-        } else {
-            PLAYER_SPEED
-        };
+/// Where an obstacle is in its life.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ObstaclePhase {
+    /// Only its warning line is showing; it hasn't entered the field yet.
+    Warning,
+    /// Somewhere in the field, from the tick it enters until it has completely left.
+    Active,
+    /// Completely gone from the field.
+    Exiting,
+}
+
+/// Obstacles are positioned in rixels, which run clockwise around the border of the playfield
+/// starting at the upper-left corner, with `W = FIELD_WIDTH` and `H = FIELD_HEIGHT`:
+///
+/// ```text
+///        0 ---- North ---> W
+///        ^                 |
+///        |                 |
+///      West              East
+///        |                 |
+///        |                 v
+///   2W + H <--- South ---- W + H
+/// ```
+///
+/// West ends at 2W + 2H, back where North starts. An obstacle enters at its rixel and leaves through
+/// `opposite()`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Obstacle {
+    /// Unique within a session, in spawn order.
+    id: u64,
+    /// A measurement of where the obstacle is coming from. 1 rixel = 1 pixel around the perimeter
+    /// of the playfield, starting at the upper-left corner.
+    rixel: RixelCoord,
+    /// The side `rixel` is on.
+    side: Direction,
+    /// Speed as the obstacle enters the field.
+    speed: f32,
+    /// How much `speed` goes up each tick. Zero for everything but accelerating obstacles.
+    #[serde(default)]
+    acceleration: f32,
+    width: f32,
+    length: f32,
+    lifetime: f32,
+    kind: ObstacleKind,
+    /// Whether this obstacle has overlapped the player yet.
+    touched_player: bool,
+    /// Whether this obstacle has overlapped the collector yet.
+    #[serde(default)]
+    grazed: bool,
+}
 
-        // Check movement.
-        if self.reset_countdown.is_none() {
-            if keyboard[Key::H].is_down() || keyboard[Key::Left].is_down() {
-                self.player.rect.pos.x -= movespeed;
-            } else if keyboard[Key::J].is_down() || keyboard[Key::Down].is_down() {
-                self.player.rect.pos.y += movespeed;
-            } else if keyboard[Key::K].is_down() || keyboard[Key::Up].is_down() {
-                self.player.rect.pos.y -= movespeed;
-            } else if keyboard[Key::L].is_down() || keyboard[Key::Right].is_down() {
-                self.player.rect.pos.x += movespeed;
+impl Obstacle {
+    /// Randomly generate a new obstacle coming in from `side`.
+    fn spawn(
+        id: u64,
+        side: Direction,
+        rng: &mut StdRng,
+        score: u32,
+        settings: &DifficultySettings,
+    ) -> Obstacle {
+        let kind = ObstacleKind::random(rng, score);
+        let (mut speed, mut acceleration, mut width, mut length) = (
+            settings.obstacle_speed(score),
+            0.,
+            rng.gen_range(6.0, 14.0),
+            OBSTACLE_BASE_LENGTH,
+        );
+        match kind {
+            ObstacleKind::Fast => {
+                speed *= FAST_OBSTACLE_SPEED_FACTOR;
+                length *= FAST_OBSTACLE_LENGTH_FACTOR;
+            }
+            ObstacleKind::Wide => {
+                speed *= WIDE_OBSTACLE_SPEED_FACTOR;
+                width *= WIDE_OBSTACLE_WIDTH_FACTOR;
             }
+            ObstacleKind::Accelerating => {
+                speed *= ACCELERATING_OBSTACLE_START_SPEED_FACTOR;
+                acceleration = ACCELERATING_OBSTACLE_ACCELERATION;
+            }
+            ObstacleKind::Normal
+            | ObstacleKind::Bonus
+            | ObstacleKind::Blocker
+            | ObstacleKind::Gold
+            | ObstacleKind::Boss => {}
         }
 
-        // Put player back in movement bounds.
-        if self.player.rect.pos.x + self.player.rect.size.x > FIELD_EDGE_LENGTH {
-            self.player.rect.pos.x = FIELD_EDGE_LENGTH - self.player.rect.size.x;
-        } else if self.player.rect.pos.x < 0. {
-            self.player.rect.pos.x = 0.;
-        }
-        if self.player.rect.pos.y + self.player.rect.size.y > FIELD_EDGE_LENGTH {
-            self.player.rect.pos.y = FIELD_EDGE_LENGTH - self.player.rect.size.y;
-        } else if self.player.rect.pos.y < 0. {
-            self.player.rect.pos.y = 0.;
+        // Keep half the obstacle's width clear of both corners of the chosen side, so the whole
+        // obstacle stays on that side instead of straddling a corner.
+        let rixel = RixelCoord(
+            side.side_start() + rng.gen_range(width / 2., side.side_length() - width / 2.),
+        );
+        Obstacle {
+            id,
+            rixel,
+            side,
+            speed,
+            acceleration,
+            width,
+            length,
+            lifetime: -(OBSTACLE_PRE_SPAWN_WARN_TIME as f32),
+            kind,
+            touched_player: false,
+            grazed: false,
         }
+    }
 
-        // Quit and shit.
-        if keyboard[Key::Escape].is_down() {
-            self.is_running = false;
+    /// Generate `count` obstacles at once, with ids counting up from `first_id`, spread roughly
+    /// evenly around the field. Each side gets obstacles in proportion to its length, and every
+    /// side gets at least one when `count >= 4`.
+    fn spawn_wave(
+        first_id: u64,
+        count: usize,
+        rng: &mut StdRng,
+        score: u32,
+        settings: &DifficultySettings,
+    ) -> Vec<Obstacle> {
+        let offset: f32 = rng.gen_range(0., 1.);
+        let mut per_side = [0; 4];
+        for i in 0..count {
+            let rixel = from_normalized((offset + i as f32 / count as f32).fract());
+            // Rounding can land right on the perimeter itself, which is back at the start.
+            let side = Self::rixel_to_direction(rixel).unwrap_or(Direction::North);
+            per_side[side as usize] += 1;
+        }
+        // A short side can fall between two evenly spaced points, so borrow from the busiest side.
+        if count >= 4 {
+            while let Some(empty) = per_side.iter().position(|&n| n == 0) {
+                let busiest = (0..4).max_by_key(|&i| per_side[i]).unwrap();
+                per_side[busiest] -= 1;
+                per_side[empty] += 1;
+            }
         }
 
-        Ok(())
+        let mut obstacles = Vec::with_capacity(count);
+        for (&side, &n) in Direction::ALL.iter().zip(&per_side) {
+            let slot = side.side_length() / n as f32;
+            for j in 0..n {
+                let mut ob = Obstacle::spawn(
+                    first_id + obstacles.len() as u64,
+                    side,
+                    rng,
+                    score,
+                    settings,
+                );
+                let jitter = rng.gen_range(-SPAWN_WAVE_JITTER, SPAWN_WAVE_JITTER) * slot;
+                let along = (slot * (j as f32 + 0.5) + jitter)
+                    .max(ob.width / 2.)
+                    .min(side.side_length() - ob.width / 2.);
+                ob.rixel = RixelCoord(side.side_start() + along);
+                obstacles.push(ob);
+            }
+        }
+        obstacles
     }
 
-    fn update_fps_graph(&mut self, window: &Window) -> Result<()> {
-        self.fps_graph.log_fps(window.current_fps());
-        if self.fps_update_time.is_none()
-            || Instant::now().duration_since(self.fps_update_time.unwrap())
-            > Duration::from_millis(200)
-        {
-            self.fps_update_time = Some(Instant::now());
+    /// Generate a boss obstacle coming in from `side`.
+    fn spawn_boss(
+        id: u64,
+        side: Direction,
+        rng: &mut StdRng,
+        score: u32,
+        settings: &DifficultySettings,
+    ) -> Obstacle {
+        let width = side.side_length() * BOSS_OBSTACLE_WIDTH_FACTOR;
+        let rixel = RixelCoord(
+            side.side_start() + rng.gen_range(width / 2., side.side_length() - width / 2.),
+        );
+        Obstacle {
+            id,
+            rixel,
+            side,
+            speed: settings.obstacle_speed(score) * BOSS_OBSTACLE_SPEED_FACTOR,
+            acceleration: 0.,
+            width,
+            length: OBSTACLE_BASE_LENGTH,
+            lifetime: -(BOSS_PRE_SPAWN_WARN_TIME as f32),
+            kind: ObstacleKind::Boss,
+            touched_player: false,
+            grazed: false,
         }
-
-        Ok(())
     }
 
-    fn update_check_collisions(&mut self) -> Result<()> {
-        if self.reset_countdown.is_none() {
-            for ob in &mut self.obstacles {
-                ob.lifetime += 1.;
-
-                // Check collisions.
-                if self.player.rect.overlaps_rectangle(&ob.rectangle()) {
-                    self.reset_countdown = Some(Countdown::new(Duration::from_secs(2)));
-                } else if self
-                    .player
-                    .collector_rectangle()
-                    .overlaps_rectangle(&ob.rectangle())
-                {
-                    self.player.score += 1;
-                }
-            }
-        }
+    /// Convert a numerical position (in rixels) to a side of the screen.
+    fn rixel_to_direction(rixel: RixelCoord) -> Result<Direction> {
+        Direction::of_rixel(rixel, &FieldGeometry::default())
+            .ok_or(Error::ObstacleRixelOutOfBounds(rixel.0))
+    }
 
-        Ok(())
+    /// How far around the border the obstacle's rixel is, from 0 at the upper-left corner up to
+    /// but not including 1.
+    #[cfg(feature = "debug")]
+    fn rixel_normalized(&self) -> f32 {
+        self.rixel.0 / FIELD_PERIMETER
     }
 
-    fn update_spawn_obstacles(&mut self) -> Result<()> {
-        if self.last_spawned.is_none() || self.last_spawned.unwrap().elapsed() > self.spawn_interval
-        {
-            self.last_spawned = Some(Instant::now());
-            self.obstacles.push(Obstacle::spawn(&mut self.rng));
-            self.spawn_interval = GameState::obstacle_spawn_interval(self.player.score);
-        }
+    /// Convert obstacle positioning data (the side and rixel it enters at, distance from the edge,
+    /// and dimensions) to a rectangle. `rixel` has to be on `side`.
+    fn side_rectangle(
+        side: Direction,
+        rixel: RixelCoord,
+        distance: f32,
+        length: f32,
+        width: f32,
+    ) -> Rectangle {
+        let along = side.clockwise_vector();
+        let out = side.unit_vector();
+        // Where the rixel is on the border, then the middle of the rectangle's front and back
+        // ends, `distance` and `distance - length` into the field.
+        let entry = side.start_corner() + along * rixel.along(side);
+        let front = entry - out * distance;
+        let back = front + out * length;
 
-        Ok(())
+        let half_width = along * (width / 2.);
+        let (a, b) = (front - half_width, back + half_width);
+        let min = Vector::new(a.x.min(b.x), a.y.min(b.y));
+        let max = Vector::new(a.x.max(b.x), a.y.max(b.y));
+        Rectangle::new(min, max - min)
     }
 
-    fn update_reset_game(&mut self) -> Result<()> {
-        if let Some(c) = &self.reset_countdown {
-            if c.is_done() {
-                println!("You lose! Score: {}", self.player.score);
-                self.obstacles.clear();
-                self.player = Player::new();
-                self.reset_countdown = None;
-            }
-        }
-
-        Ok(())
+    /// Get this obstacle's rectangle.
+    fn rectangle(&self) -> Rectangle {
+        let (distance, length) = self.extent_at(self.lifetime);
+        Obstacle::side_rectangle(self.side, self.rixel, distance, length, self.width)
     }
 
-    fn update_despawn_obstacles(&mut self) -> Result<()> {
-        // Give the player points and destroy an obstacle if it's offscreen.
-        let player = &mut self.player;
-        self.obstacles.retain(|&ob| {
-            let res = ob.lifetime
-                < ob.total_lifetime()
-                + FIELD_EDGE_LENGTH / OBSTACLE_WARNING_MOVE_SPEED
-                + OBSTACLE_HIDE_DELAY as f32;
-            if !res {
-                player.score += 100;
-            }
-            res
-        });
+    /// How far the front of the obstacle is into the field `lifetime` ticks after it spawned, and
+    /// how much of it is inside the field. The obstacle grows while it enters, keeps its full
+    /// length while crossing, then shrinks as it leaves. Before it spawns and after it has left,
+    /// nothing of it is in the field.
+    ///
+    /// Both are clamped, to `[0, crossing_distance()]` and `[0, length]`, so the rectangle built
+    /// from them never has a negative size even when float error puts `lifetime` right on a
+    /// phase boundary.
+    fn extent_at(&self, lifetime: f32) -> (f32, f32) {
+        let crossing = self.crossing_distance();
+        let total = self.total_lifetime();
+        let travelled = self.distance_at(lifetime.clamp(0., total));
+        let distance = travelled.clamp(0., crossing);
 
-        Ok(())
+        let length = if lifetime < 0. || lifetime > total {
+            0.
+        } else if travelled < self.length {
+            travelled
+        } else if travelled > crossing {
+            self.length - (travelled - crossing)
+        } else {
+            self.length
+        };
+
+        (distance, length.clamp(0., self.length))
     }
-}
 
-impl State for GameState {
-    fn new() -> quicksilver::Result<GameState> {
-        Ok(GameState {
-            obstacles: Vec::new(),
-            player: Player::new(),
-            rng: rand::thread_rng(),
+    /// Whether a player of `player_size` with its top-left corner at `pos` would be touching this
+    /// obstacle where it is right now.
+    fn would_collide_with_player_at(&self, pos: Vector, player_size: Vector) -> bool {
+        self.hitbox()
+            .is_some_and(|rect| rect.overlaps_rectangle(&Rectangle::new(pos, player_size)))
+    }
 
-            is_running: true,
-            reset_countdown: None,
+    /// The part of this obstacle that can touch anything, or `None` while none of it is in the
+    /// field. An obstacle that is only being telegraphed, or has just left, is an empty rectangle
+    /// on the border, which would still overlap anything straddling the border.
+    fn hitbox(&self) -> Option<Rectangle> {
+        Some(self.rectangle()).filter(|rect| rect.width() > 0. && rect.height() > 0.)
+    }
 
-            fps_graph: FpsGraph::new(),
-            fps_update_time: None,
+    /// Get the rixel directly across the field, where this obstacle leaves it.
+    fn opposite(&self) -> RixelCoord {
+        // Facing sides run in opposite directions, so the further along its side the entry is,
+        // the less far along the opposite side the exit is.
+        let exit_side = self.side.opposite();
+        let exit = exit_side.side_start() + exit_side.side_length() - self.rixel.along(self.side);
+        // Entering right at the start of East lands exactly on the end of West, which wraps
+        // around to 0.
+        RixelCoord(exit.rem_euclid(FIELD_PERIMETER))
+    }
 
-            last_spawned: None,
-            spawn_interval: Duration::new(4, 0),
+    /// How far this obstacle travels to get across the field.
+    fn crossing_distance(&self) -> f32 {
+        self.side.crossing_distance()
+    }
 
-            font: Asset::new(Font::load(FONT_NAME)),
-            font_style: FontStyle::new(FONT_SIZE_PT, Color::WHITE),
-        })
+    /// How far the front of the obstacle has come into the field, from 0 as it enters to
+    /// `crossing_distance() + length` once it has left.
+    fn travelled(&self) -> f32 {
+        self.distance_at(self.age_fraction() * self.total_lifetime())
     }
 
-    fn update(&mut self, window: &mut Window) -> quicksilver::Result<()> {
-        fn update_inner(state: &mut GameState, window: &mut Window) -> Result<()> {
-            if !state.is_running {
-                window.close();
+    /// How far the front of the obstacle has come into the field `t` ticks after it entered.
+    fn distance_at(&self, t: f32) -> f32 {
+        match self.kind {
+            ObstacleKind::Blocker => {
+                // Park with the middle of the obstacle over the middle of the field.
+                let park = self.total_distance() / 2.;
+                let park_time = park / self.speed;
+                if t < park_time {
+                    self.speed * t
+                } else if t < park_time + BLOCKER_OBSTACLE_DWELL_TICKS {
+                    park
+                } else {
+                    self.speed * (t - BLOCKER_OBSTACLE_DWELL_TICKS)
+                }
             }
+            _ => self.speed * t + 0.5 * self.acceleration * t * t,
+        }
+    }
 
-            state.update_handle_input(window.keyboard())?;
-            state.update_fps_graph(window)?;
-            state.update_check_collisions()?;
-            state.update_spawn_obstacles()?;
-            state.update_despawn_obstacles()?;
-            state.update_reset_game()?;
+    /// How far the front of the obstacle moves between entering the field and having completely
+    /// left it.
+    fn total_distance(&self) -> f32 {
+        self.crossing_distance() + self.length
+    }
 
-            Ok(())
+    /// The lifetime value at which this obstacle has moved completely offscreen.
+    fn total_lifetime(&self) -> f32 {
+        let distance = self.total_distance();
+        if self.kind == ObstacleKind::Blocker {
+            distance / self.speed + BLOCKER_OBSTACLE_DWELL_TICKS
+        } else if self.acceleration == 0. {
+            distance / self.speed
+        } else {
+            // Solve `speed * t + acceleration * t^2 / 2 = distance` for t.
+            ((self.speed * self.speed + 2. * self.acceleration * distance).sqrt() - self.speed)
+                / self.acceleration
         }
+    }
 
-        update_inner(self, window).or_else(|e| {
-            match e {
-                Error::QuicksilverError(e) => return Err(e),
-                _ => panic!(e.to_string())
-            }
-        })
+    /// The front end of an accelerating obstacle while it is still in the field, drawn brighter
+    /// so it stands out from one moving at a steady speed.
+    fn leading_edge(&self) -> Option<Rectangle> {
+        let travelled = self.travelled();
+        if self.acceleration == 0.
+            || self.phase() != ObstaclePhase::Active
+            || travelled > self.crossing_distance()
+        {
+            return None;
+        }
+        let length = travelled.min(OBSTACLE_LEADING_EDGE_LENGTH);
+        Some(Obstacle::side_rectangle(
+            self.side,
+            self.rixel,
+            travelled,
+            length,
+            self.width,
+        ))
     }
 
-    fn draw(&mut self, window: &mut Window) -> quicksilver::Result<()> {
-        fn draw_inner(state: &mut GameState, window: &mut Window) -> Result<()> {
-            window.clear(Color::BLACK)?;
+    /// How far through its crossing the obstacle is, from 0 as it enters to 1 once it has left.
+    fn age_fraction(&self) -> f32 {
+        (self.lifetime / self.total_lifetime()).clamp(0., 1.)
+    }
 
-            state.draw_field_border(window)?;
-            state.draw_player(window)?;
-            state.draw_obstacles(window)?;
-            state.draw_hud(window)?;
+    /// How many ticks the obstacle is telegraphed for before it enters the field.
+    fn pre_spawn_warn_time(&self) -> f32 {
+        match self.kind {
+            ObstacleKind::Boss => BOSS_PRE_SPAWN_WARN_TIME as f32,
+            _ => OBSTACLE_PRE_SPAWN_WARN_TIME as f32,
+        }
+    }
 
-            Ok(())
+    /// Where the obstacle is in its life. It is active from the tick it enters, at a lifetime of
+    /// 0, up to and including `total_lifetime()`.
+    fn phase(&self) -> ObstaclePhase {
+        if self.lifetime < 0. {
+            ObstaclePhase::Warning
+        } else if self.lifetime <= self.total_lifetime() {
+            ObstaclePhase::Active
+        } else {
+            ObstaclePhase::Exiting
         }
+    }
 
-        draw_inner(self, window).or_else(|e| {
-            match e {
-                Error::QuicksilverError(e) => return Err(e),
-                _ => panic!(e.to_string())
-            }
-        })
+    /// How fast the obstacle comes in next to the range `settings` sends them at, from 0 at the
+    /// preset's base speed or slower up to 1 for a fast obstacle at its top speed.
+    fn urgency(&self, settings: &DifficultySettings) -> f32 {
+        let slowest = settings.obstacle_speed_base;
+        let fastest = settings.obstacle_speed_max * FAST_OBSTACLE_SPEED_FACTOR;
+        ((self.speed - slowest) / (fastest - slowest)).clamp(0., 1.)
     }
-}
 
-/// Converts world-centric positioning to playfield-centric positioning.
-trait ToPlayfieldCoordinates {
-    fn on_playfield(&self) -> Rectangle;
-}
+    /// Where the warning line telegraphing this obstacle is: the rixel it starts from, how far
+    /// into the field its far end is, and how long it is. It sweeps in from the obstacle's side
+    /// before it spawns and stays while it crosses.
+    ///
+    /// `None` on the tick the obstacle is first telegraphed, before the line has any length, and
+    /// once the obstacle has left, when an `ExitSweep` takes over.
+    fn warning_geometry(&self) -> Option<(RixelCoord, f32, f32)> {
+        let crossing = self.crossing_distance();
+        let length = match self.phase() {
+            ObstaclePhase::Warning => crossing
+                .min(OBSTACLE_WARNING_MOVE_SPEED * (self.lifetime + self.pre_spawn_warn_time())),
+            ObstaclePhase::Active => crossing,
+            ObstaclePhase::Exiting => return None,
+        };
+        if length <= 0. {
+            return None;
+        }
+        // The line always reaches back to the border.
+        Some((self.rixel, length, length))
+    }
+
+    /// The warning line telegraphing this obstacle, `width` thick, if it has one. See
+    /// `warning_geometry`.
+    fn warning(&self, width: f32) -> Option<ObstacleWarning> {
+        let (rixel, distance, length) = self.warning_geometry()?;
+        Some(ObstacleWarning {
+            side: self.side,
+            rixel,
+            distance,
+            length,
+            width,
+        })
+    }
+
+    /// The rectangles making up this obstacle's warning line.
+    fn warning_rects(&self, width: f32) -> ObstacleWarningIter {
+        ObstacleWarningIter {
+            next: self.warning(width),
+        }
+    }
+
+    /// Ticks the obstacle has left before it is gone.
+    fn remaining_lifetime(&self) -> f32 {
+        self.total_lifetime() - self.lifetime
+    }
+
+    /// Where this obstacle is drawn relative to `other`: ones still being telegraphed first, then
+    /// the rest by how long they have left, most first. An obstacle about to leave sorts last and
+    /// is drawn on top.
+    fn draw_order(&self, other: &Obstacle) -> Ordering {
+        let warning = |ob: &Obstacle| ob.phase() == ObstaclePhase::Warning;
+        warning(other).cmp(&warning(self)).then_with(|| {
+            other
+                .remaining_lifetime()
+                .partial_cmp(&self.remaining_lifetime())
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+/// The warning line an obstacle leaves behind once it has crossed the field. It stays put for
+/// `OBSTACLE_HIDE_DELAY` ticks, then retreats into the side the obstacle left through. Only for
+/// show; the obstacle is already gone and scored.
+#[derive(Debug, Clone, Copy)]
+struct ExitSweep {
+    side: Direction,
+    /// Where the obstacle came in.
+    rixel: RixelCoord,
+    /// Where it left, directly across from `rixel`.
+    exit_rixel: RixelCoord,
+    crossing: f32,
+    /// Ticks since the obstacle left the field.
+    age: f32,
+}
+
+impl ExitSweep {
+    fn new(ob: &Obstacle) -> Self {
+        ExitSweep {
+            side: ob.side,
+            rixel: ob.rixel,
+            exit_rixel: ob.opposite(),
+            crossing: ob.crossing_distance(),
+            age: 0.,
+        }
+    }
+
+    fn warning(&self) -> ObstacleWarning {
+        let retreat_age = self.age - OBSTACLE_HIDE_DELAY as f32;
+        let (side, rixel, length) = if retreat_age < 0. {
+            (self.side, self.rixel, self.crossing)
+        } else {
+            let dist = self.crossing - retreat_age * OBSTACLE_WARNING_MOVE_SPEED;
+            (self.side.opposite(), self.exit_rixel, dist.max(0.))
+        };
+
+        ObstacleWarning {
+            side,
+            rixel,
+            distance: length,
+            length,
+            width: OBSTACLE_WARNING_WIDTH,
+        }
+    }
+
+    fn warning_rects(&self) -> ObstacleWarningIter {
+        ObstacleWarningIter {
+            next: Some(self.warning()),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.age >= OBSTACLE_HIDE_DELAY as f32 + self.crossing / OBSTACLE_WARNING_MOVE_SPEED
+    }
+}
+
+/// A segment of an obstacle's warning line, `length` long with its far end `distance` into the
+/// field from `rixel`.
+#[derive(Debug, Clone, Copy)]
+struct ObstacleWarning {
+    /// The side `rixel` is on.
+    side: Direction,
+    rixel: RixelCoord,
+    distance: f32,
+    length: f32,
+    width: f32,
+}
+
+impl ObstacleWarning {
+    fn rectangle(&self) -> Rectangle {
+        // Didn't realize Quicksilver had a Line type lol.
+        Obstacle::side_rectangle(self.side, self.rixel, self.distance, self.length, self.width)
+    }
+}
+
+/// Yields the rectangles of an obstacle's warning line. See `Obstacle::warning_rects`.
+struct ObstacleWarningIter {
+    next: Option<ObstacleWarning>,
+}
+
+impl Iterator for ObstacleWarningIter {
+    type Item = Rectangle;
+
+    fn next(&mut self) -> Option<Rectangle> {
+        self.next.take().map(|warning| warning.rectangle())
+    }
+}
+
+/// A frame that took much longer than the ones before it.
+#[derive(Debug, Clone, Copy)]
+struct FrameSpike {
+    tick: u64,
+    frame_ms: f64,
+}
+
+impl fmt::Display for FrameSpike {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {:.1}ms frame", self.tick, self.frame_ms)
+    }
+}
+
+/// Which top-level screen the game is showing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Screen {
+    Title,
+    Playing,
+    GameOver,
+    /// Something went wrong mid-run. Shows the error until the player resets or quits.
+    Error,
+}
+
+/// How much purely cosmetic drawing the game does, lowered while the frame rate is struggling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Quality {
+    Full,
+    /// No exit sweeps, border ripples, backdrop or player trail, and the boss and safe zone stop
+    /// pulsing.
+    Reduced,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Mode {
+    Classic,
+    /// Obstacles pass through the player instead of ending the run.
+    Practice,
+    /// Classic rules, on a seed that is the same for everyone playing on a given day.
+    Daily,
+}
+
+impl Mode {
+    const ALL: [Mode; 3] = [Mode::Classic, Mode::Practice, Mode::Daily];
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Classic => "Classic",
+            Mode::Practice => "Practice",
+            Mode::Daily => "Daily",
+        }
+    }
+}
+
+/// Something that happened to an obstacle, kept for debugging spawn behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+enum ObstacleEvent {
+    Spawned {
+        id: u64,
+        rixel: f32,
+        speed: f32,
+        width: f32,
+        tick: u64,
+    },
+    /// The obstacle overlapped the player for the first time.
+    TouchedPlayer { id: u64, tick: u64 },
+    Despawned { id: u64, tick: u64 },
+}
+
+/// Something that happened during a tick that the rest of the game may react to. Update functions
+/// queue these instead of touching the score or effects themselves, and `process_events` applies
+/// them all in one place.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+enum GameEventKind {
+    ObstacleSpawned {
+        rixel: f32,
+        speed: f32,
+    },
+    /// A lethal obstacle ran into the player.
+    PlayerHit,
+    /// An obstacle left the field, worth `bonus` points.
+    ObstacleDespawned { kind: ObstacleKind, bonus: u32 },
+    /// The collector spent a tick over an obstacle. `first` is set on the first tick it touched
+    /// that obstacle.
+    Grazed { kind: ObstacleKind, first: bool },
+    /// Points earned some other way, e.g. from a bomb.
+    ScoreChanged { points: u32 },
+    /// The player has now survived `seconds`, a multiple of `SURVIVAL_MILESTONE_SECS`.
+    SurvivalMilestone {
+        seconds: u64,
+    },
+}
+
+/// A game event as kept in the run's event log.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct GameEvent {
+    tick: u64,
+    kind: GameEventKind,
+}
+
+/// A short flash on the field border where an obstacle has just entered.
+struct BorderRipple {
+    side: Direction,
+    rixel: RixelCoord,
+    /// Width of the obstacle that caused the ripple.
+    width: f32,
+    /// Game time the ripple started at.
+    birth: f64,
+}
+
+impl BorderRipple {
+    /// How far through its animation the ripple is, from 0 to 1.
+    fn progress(&self, clock: &impl Clock) -> f32 {
+        clock.since(self.birth).as_millis() as f32 / BORDER_RIPPLE_DURATION_MS as f32
+    }
+}
+
+/// Text that floats up from a spot on the field and fades out, e.g. for a points bonus.
+struct ScorePopup {
+    text: String,
+    /// Where the popup starts, in playfield coordinates.
+    pos: Vector,
+    /// Game time the popup appeared at.
+    birth: f64,
+}
+
+impl ScorePopup {
+    /// How far through its animation the popup is, from 0 to 1.
+    fn progress(&self, clock: &impl Clock) -> f32 {
+        clock.since(self.birth).as_millis() as f32 / SCORE_POPUP_DURATION_MS as f32
+    }
+}
+
+/// Tracks information about the player and their avatar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Player {
+    rect: Rectangle,
+    velocity: Vector,
+    score: u32,
+    /// How much slow-mo is left, from 0 to 1.
+    stamina: f32,
+    /// Set when stamina runs out, until it has recharged enough to use again.
+    exhausted: bool,
+    /// Bombs stored for later, triggered with X.
+    bombs: u32,
+    /// Hits left before the run ends.
+    lives: u8,
+    /// Running while the collector flashes after starting to graze an obstacle.
+    #[serde(skip)]
+    graze_flash: Option<Countdown>,
+    /// Running while a magnet has the collector enlarged.
+    #[serde(skip)]
+    magnet: Option<Countdown>,
+    /// Edge length of the collector, which grows while a magnet is active.
+    #[serde(skip, default = "default_collector_edge")]
+    collector_edge: f32,
+    /// Game time and position of the player's center over the last `CAMPING_WINDOW_MS`, oldest
+    /// first.
+    #[serde(skip)]
+    position_history: VecDeque<(f64, Vector)>,
+    /// Points owed to camping pressure that haven't added up to a whole point yet.
+    #[serde(skip)]
+    score_drain: f32,
+    /// Colors to draw the player with in place of the color scheme's. Carried over from one run
+    /// to the next.
+    #[serde(skip)]
+    skin: Option<Skin>,
+    /// Where the player's center was over the last `PLAYER_TRAIL_LENGTH` ticks, oldest first.
+    #[serde(skip)]
+    trail: VecDeque<Vector>,
+}
+
+fn default_collector_edge() -> f32 {
+    COLLECTOR_EDGE_LENGTH
+}
+
+impl Player {
+    fn new(settings: &DifficultySettings, skin: Option<Skin>) -> Player {
+        Player {
+            rect: Rectangle::new((0., 0.), (PLAYER_SIZE, PLAYER_SIZE)),
+            velocity: Vector::ZERO,
+            score: 0,
+            stamina: 1.,
+            exhausted: false,
+            bombs: 0,
+            lives: settings.player_lives.max(1),
+            graze_flash: None,
+            magnet: None,
+            collector_edge: COLLECTOR_EDGE_LENGTH,
+            position_history: VecDeque::new(),
+            score_drain: 0.,
+            skin,
+            trail: VecDeque::new(),
+        }
+    }
+
+    /// Add to the score, earning a bomb for each multiple of `BOMB_SCORE_INTERVAL` crossed.
+    fn add_score(&mut self, points: u32) {
+        let before = self.score / BOMB_SCORE_INTERVAL;
+        self.score += points;
+        self.bombs += self.score / BOMB_SCORE_INTERVAL - before;
+    }
+
+    fn collector_rectangle(&self) -> Rectangle {
+        Rectangle::new_sized((self.collector_edge, self.collector_edge))
+            .with_center(self.rect.center())
+    }
+
+    /// Start a magnet, or make the one already running last `MAGNET_DURATION_MS` longer.
+    fn add_magnet(&mut self, clock: &impl Clock) {
+        let duration = Duration::from_millis(MAGNET_DURATION_MS);
+        if let Some(magnet) = self.magnet.as_mut().filter(|m| !m.is_done(clock)) {
+            magnet.extend(duration);
+        } else {
+            self.magnet = Some(Countdown::new(clock, duration));
+        }
+    }
+
+    /// Size the collector for how much of the magnet is left, shrinking it back over the last
+    /// `MAGNET_SHRINK_MS`.
+    fn update_collector(&mut self, clock: &impl Clock) {
+        let remaining = self
+            .magnet
+            .as_ref()
+            .map_or(Duration::from_secs(0), |magnet| magnet.remaining(clock));
+        let grown = (remaining.as_millis() as f32 / MAGNET_SHRINK_MS as f32).min(1.);
+        let max_edge = COLLECTOR_EDGE_LENGTH * MAGNET_COLLECTOR_FACTOR;
+        self.collector_edge = COLLECTOR_EDGE_LENGTH.lerp(&max_edge, grown);
+        if remaining == Duration::from_secs(0) {
+            self.magnet = None;
+        }
+    }
+
+    /// Note where the player is, forgetting anything older than `CAMPING_WINDOW_MS` except the
+    /// one entry needed to cover the whole window.
+    fn record_position(&mut self, clock: &impl Clock) {
+        let now = clock.now();
+        let window = Duration::from_millis(CAMPING_WINDOW_MS).as_secs_f64();
+        self.position_history.push_back((now, self.rect.center()));
+        while self.position_history.len() > 1 && now - self.position_history[1].0 >= window {
+            self.position_history.pop_front();
+        }
+    }
+
+    /// Take `CAMPING_DRAIN_PER_SEC` points per second away for `dt`, never going below zero.
+    fn drain_score(&mut self, dt: Duration) {
+        self.score_drain += CAMPING_DRAIN_PER_SEC * dt.as_secs_f32();
+        let points = self.score_drain.floor();
+        self.score_drain -= points;
+        self.score = self.score.saturating_sub(points as u32);
+    }
+
+    /// Move the player's top-left corner to `pos`, kept inside `field`. Returns which axes had to
+    /// be pulled back in.
+    fn set_position(&mut self, pos: Vector, field: &FieldGeometry) -> BoundaryHit {
+        self.rect.pos = pos;
+        field.clamp_to_bounds(&mut self.rect);
+        BoundaryHit {
+            x: self.rect.pos.x != pos.x,
+            y: self.rect.pos.y != pos.y,
+        }
+    }
+
+    /// Move the player's top-left corner to `pos`, wrapped around to the other side of `field` if
+    /// it is off an edge.
+    fn wrap_position(&mut self, pos: Vector, field: &FieldGeometry) {
+        self.rect.pos = Vector::new(
+            pos.x.rem_euclid(field.width),
+            pos.y.rem_euclid(field.height),
+        );
+    }
+
+    /// Add where the player is now to the trail, dropping the oldest position once it's full.
+    fn record_trail(&mut self) {
+        self.trail.push_back(self.rect.center());
+        while self.trail.len() > PLAYER_TRAIL_LENGTH {
+            self.trail.pop_front();
+        }
+    }
+
+    /// Everywhere the player is on the field. Normally just `rect`, but a player wrapping off
+    /// the right or bottom edge is also partly back on the left or top.
+    fn rects(&self, field: &FieldGeometry) -> Vec<Rectangle> {
+        let rect = self.rect;
+        let over_x = rect.x() + rect.width() > field.width;
+        let over_y = rect.y() + rect.height() > field.height;
+        let mut rects = vec![rect];
+        if over_x {
+            rects.push(rect.translate((-field.width, 0.)));
+        }
+        if over_y {
+            rects.push(rect.translate((0., -field.height)));
+        }
+        if over_x && over_y {
+            rects.push(rect.translate((-field.width, -field.height)));
+        }
+        rects
+    }
+
+    fn overlaps(&self, field: &FieldGeometry, other: &Rectangle) -> bool {
+        self.rects(field)
+            .iter()
+            .any(|r| r.overlaps_rectangle(other))
+    }
+}
+
+/// Which axes the player ran into the edge of the field on.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct BoundaryHit {
+    x: bool,
+    y: bool,
+}
+
+/// What the player is asking their avatar to do this tick.
+struct MovementInput {
+    /// Unit vector in the direction being held, or zero.
+    direction: Vector,
+    slowmo: bool,
+}
+
+/// Move the player for `dt` ticks: accelerate in the held direction, slow down with friction, and
+/// keep the speed under the (possibly slow-mo) cap. The player stays inside `field`, or with
+/// `wrap` set, comes back in on the other side after going off an edge.
+fn apply_movement(
+    input: &MovementInput,
+    player: &mut Player,
+    field: &FieldGeometry,
+    dt: f32,
+    wrap: bool,
+) -> BoundaryHit {
+    let cap = if input.slowmo {
+        PLAYER_SPEED / PLAYER_SLOWMO_FACTOR
+    } else {
+        PLAYER_SPEED
+    };
+
+    player.velocity += input.direction * (PLAYER_ACCELERATION * dt);
+    player.velocity *= PLAYER_FRICTION.powf(dt);
+    if player.velocity.len() > cap {
+        player.velocity = player.velocity.with_len(cap);
+    }
+
+    let pos = player.rect.pos + player.velocity * dt;
+    if wrap {
+        player.wrap_position(pos, field);
+        BoundaryHit::default()
+    } else {
+        player.set_position(pos, field)
+    }
+}
+
+/// The serializable part of a `GameState`, written on close so a run can be resumed. Timers are
+/// stored relative to the moment the snapshot was taken.
+#[derive(Serialize, Deserialize)]
+struct GameSnapshot {
+    /// Seconds since the Unix epoch.
+    saved_at: u64,
+    obstacles: Vec<Obstacle>,
+    player: Player,
+    spawn_interval: Duration,
+    since_last_spawn: Option<Duration>,
+    difficulty: DifficultyPreset,
+    mode: Mode,
+    #[serde(default)]
+    seed: u64,
+}
+
+impl GameSnapshot {
+    fn is_recent(&self) -> bool {
+        unix_time().saturating_sub(self.saved_at) < SNAPSHOT_MAX_AGE_SECS
+    }
+}
+
+/// Today's seed for `Mode::Daily`: the number of whole days since the Unix epoch, in UTC.
+/// Deterministic builds never read the wall clock during a run, so they always play day 0 unless
+/// `SEED_FLAG` picks another.
+fn daily_seed() -> u64 {
+    if cfg!(feature = "deterministic") {
+        0
+    } else {
+        unix_time() / SECS_PER_DAY
+    }
+}
+
+/// Seconds since the Unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_time() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Seconds since the Unix epoch. `SystemTime` isn't available on the web, so ask the browser.
+#[cfg(target_arch = "wasm32")]
+fn unix_time() -> u64 {
+    (stdweb::web::Date::now() / 1000.) as u64
+}
+
+/// Whether Alt+Enter was just pressed. The window keeps drawing in the same coordinates at any
+/// size, just scaled to fit, so nothing needs laying out again after the switch.
+fn toggle_fullscreen_pressed(keyboard: &Keyboard) -> bool {
+    let alt = keyboard[Key::LAlt].is_down() || keyboard[Key::RAlt].is_down();
+    alt && keyboard[Key::Return] == ButtonState::Pressed
+}
+
+/// The game time covered by one update tick. Quicksilver runs updates at a fixed rate, given in
+/// milliseconds. Deterministic builds ignore the window's update rate so game time depends only
+/// on the tick count.
+fn tick_duration(window: &Window) -> Duration {
+    if cfg!(feature = "deterministic") {
+        Duration::from_micros(1_000_000 / DETERMINISTIC_TICKS_PER_SECOND)
+    } else {
+        Duration::from_micros((window.update_rate() * 1000.) as u64)
+    }
+}
+
+struct GameState {
+    screen: Screen,
+    /// The error being shown on the error screen.
+    error: Option<Error>,
+    difficulty: DifficultyPreset,
+    mode: Mode,
+
+    /// Always `FieldGeometry::default()`. See `FieldGeometry` for why it can't be anything else.
+    field: FieldGeometry,
+    obstacles: Vec<Obstacle>,
+    /// Rebuilt every tick from `obstacles`, holding their indices, to find the ones near the
+    /// player without checking every obstacle.
+    obstacle_grid: SpatialGrid<usize>,
+    ripples: Vec<BorderRipple>,
+    exit_sweeps: Vec<ExitSweep>,
+    /// Set from the config. Whether obstacles leave an `ExitSweep` behind.
+    show_exit_sweeps: bool,
+    /// Set from the config. Whether the player wraps around the edges of the field instead of
+    /// stopping at them.
+    wrap_edges: bool,
+    /// Set from the config. Whether the mouse and touch move the player.
+    mouse_control: bool,
+    /// Where the player is being dragged to, in playfield coordinates, while the mouse button or
+    /// a finger is held down.
+    touch_target: Option<Vector>,
+    player: Player,
+    /// Whether slow-mo was in effect on the last input tick.
+    is_slowmo: bool,
+    /// Whether camping pressure was draining the score on the last tick.
+    is_camping: bool,
+    /// Seeded at the start of every run, so a run can be replayed from its seed and input.
+    rng: StdRng,
+    /// What `rng` was seeded with at the start of the run.
+    seed: u64,
+    /// Seed given with `SEED_FLAG`, played by daily runs in place of today's.
+    seed_override: Option<u64>,
+
+    /// Records the current run, if it started fresh and isn't itself a replay.
+    recorder: Option<ReplayRecorder>,
+    /// Supplies the input while a replay is playing.
+    playback: Option<ReplayPlayback>,
+    /// Set for games run without a window, by the bench and tests. These leave no files behind.
+    headless: bool,
+    /// Set while a new player is being shown the controls, in place of a normal run.
+    tutorial: Option<Tutorial>,
+
+    /// Game time is only advanced while the simulation is running. Every timer in a run (spawns,
+    /// countdowns, camping, magnets) is on this clock, and in deterministic builds it moves a fixed
+    /// step per tick, so they count ticks there. Obstacle lifetimes are counted in ticks directly.
+    clock: GameClock,
+    /// Keeps running while the simulation is paused, for things like the resume countdown.
+    ui_clock: GameClock,
+
+    last_spawned: Option<f64>,
+    spawn_interval: Duration,
+
+    /// Number of times `update` has been called.
+    tick_count: u64,
+
+    next_obstacle_id: u64,
+    obstacle_events: EventLog<ObstacleEvent>,
+    /// Queued up over a tick, then applied by `process_events`.
+    game_events: Vec<GameEventKind>,
+    /// Every game event applied this run, written to `EVENT_LOG_PATH` when it ends. Per-tick
+    /// grazing is left out, since only the first tick on each obstacle is interesting.
+    event_log: EventLog<GameEvent>,
+
+    /// Game time the last bomb went off at, for the border flash.
+    bomb_flash: Option<f64>,
+    /// Overlay drawn on top of everything else.
+    screen_flash: Option<ScreenFlash>,
+    /// Score milestones passed so far this run.
+    milestones: u32,
+    /// Multiples of `SURVIVAL_MILESTONE_SECS` survived so far this run.
+    survival_milestones: u64,
+    /// The multiple of `BOSS_SCORE_INTERVAL` the last boss obstacle came in at.
+    last_boss_score: u32,
+    /// The multiple of `MAGNET_SCORE_INTERVAL` the last magnet was handed out at.
+    last_magnet_score: u32,
+    popups: Vec<ScorePopup>,
+    /// The score the HUD shows, which counts up toward the player's real score.
+    displayed_score: f32,
+
+    is_running: bool,
+    /// Set while the player is being asked whether they really want to quit. Freezes the run.
+    quit_prompt: bool,
+    reset_countdown: Option<Countdown>,
+
+    /// Set while the simulation is frozen, e.g. because the window lost focus.
+    paused: bool,
+    /// Counts down on `ui_clock` before the simulation continues after regaining focus.
+    resume_countdown: Option<Countdown>,
+
+    fps_graph: FpsGraph,
+    /// Drops to `Reduced` below `REDUCED_QUALITY_FPS` and only comes back above
+    /// `FULL_QUALITY_FPS`, so it doesn't flip back and forth around one threshold.
+    quality: Quality,
+    frame_spikes: EventLog<FrameSpike>,
+    /// Timings for the latest frames, listed in the F3 readout.
+    diagnostics: DiagnosticsBuffer,
+    /// UI time of the most recent frame spike.
+    last_frame_spike: Option<f64>,
+    fps_update_tick: Option<u64>,
+    /// Average and 1% low FPS as shown in the HUD. Refreshed every
+    /// `FPS_UPDATE_INTERVAL_TICKS` so the numbers stay readable.
+    fps_readout: Option<(f64, f64)>,
+
+    /// Toggled with F3. Shows extra numbers useful for tuning.
+    debug: bool,
+    /// Toggled with F4. Shades the field by how dangerous it is for the player to be there.
+    danger_map: bool,
+    /// Set by F12. The next frame is saved as a screenshot once it has been drawn.
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_requested: bool,
+    /// Toggled with F2. How many ticks obstacles have covered each cell of a
+    /// `HEATMAP_GRID_SIZE` square grid over the field, counted from when it was switched on.
+    #[cfg(feature = "debug")]
+    heatmap: Option<[[u32; HEATMAP_GRID_SIZE]; HEATMAP_GRID_SIZE]>,
+    /// Most obstacles on the field at once this session.
+    peak_obstacles: RollingMax<usize>,
+
+    run_stats: RunStats,
+    session_stats: SessionStats,
+    high_scores: HighScoreTable,
+    /// Set while the player is entering initials for a run that made the high score table.
+    initials_entry: Option<InitialsEntry>,
+    /// Whether the title or game over screen is showing the high score table instead.
+    show_high_scores: bool,
+
+    scheme: ColorScheme,
+    /// Set from the config. Draws the grid and vignette inside the field.
+    show_backdrop: bool,
+    /// The backdrop's grid lines, worked out once since the field never changes size.
+    field_grid: Vec<Rectangle>,
+    font: Asset<Font>,
+    font_style: FontStyle,
+}
+
+impl GameState {
+    /// Throw away whatever is left of the last run and start a fresh one.
+    fn start_run(&mut self) {
+        let seed = match self.mode {
+            Mode::Daily => self.seed_override.unwrap_or_else(daily_seed),
+            _ => rand::thread_rng().gen(),
+        };
+        self.start_seeded_run(seed);
+        self.recorder = Some(ReplayRecorder::new(seed, self.difficulty, self.mode));
+        self.playback = None;
+    }
+
+    /// Start playing back a recorded run.
+    fn start_replay(&mut self, replay: Replay) {
+        self.difficulty = replay.difficulty;
+        self.mode = replay.mode;
+        self.start_seeded_run(replay.seed);
+        self.recorder = None;
+        self.playback = Some(ReplayPlayback::new(replay));
+    }
+
+    fn start_seeded_run(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.seed = seed;
+        self.obstacles.clear();
+        self.ripples.clear();
+        self.exit_sweeps.clear();
+        self.game_events.clear();
+        self.event_log = EventLog::new(GAME_EVENT_LOG_SIZE);
+        self.tutorial = None;
+        self.player = Player::new(&self.difficulty.settings(), self.player.skin);
+        self.displayed_score = 0.;
+        self.is_slowmo = false;
+        self.is_camping = false;
+        self.run_stats = RunStats::new();
+        self.reset_countdown = None;
+        self.last_spawned = None;
+        self.bomb_flash = None;
+        self.screen_flash = None;
+        self.milestones = 0;
+        self.survival_milestones = 0;
+        self.last_boss_score = 0;
+        self.last_magnet_score = 0;
+        self.popups.clear();
+        self.quit_prompt = false;
+        self.spawn_interval = Duration::from_millis(INITIAL_SPAWN_INTERVAL_MS);
+        self.initials_entry = None;
+        self.show_high_scores = false;
+        self.screen = Screen::Playing;
+    }
+
+    /// Switch the player to the next skin in `SKINS`, and save it to the config for next time.
+    fn cycle_skin(&mut self) {
+        self.player.skin = util::cycle(&SKINS, self.player.skin, 1);
+        let mut config = Config::load_or_default();
+        config.skin = SKINS
+            .iter()
+            .position(|&s| s == self.player.skin)
+            .unwrap_or(0);
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save skin: {}", e);
+        }
+    }
+
+    /// Start a screen flash, replacing any that is already showing.
+    fn trigger_flash(&mut self, color: Color, duration: Duration) {
+        self.screen_flash = Some(ScreenFlash::new(&self.clock, color, duration));
+    }
+
+    /// Use up a bomb, clearing every obstacle that is currently on the field for half its usual
+    /// points. Obstacles still being telegraphed are left alone.
+    fn detonate_bomb(&mut self) {
+        if self.player.bombs == 0 {
+            return;
+        }
+        self.player.bombs -= 1;
+
+        let game_events = &mut self.game_events;
+        let events = &mut self.obstacle_events;
+        let tick = self.tick_count;
+        self.obstacles.retain(|ob| {
+            let on_field = ob.phase() == ObstaclePhase::Active;
+            if on_field {
+                let points = ob.kind.despawn_points() / 2;
+                game_events.push(GameEventKind::ScoreChanged { points });
+                events.push(ObstacleEvent::Despawned { id: ob.id, tick });
+            }
+            !on_field
+        });
+
+        // Give the player a breather before the next spawn.
+        self.last_spawned = Some(self.clock.now());
+        self.bomb_flash = Some(self.clock.now());
+    }
+
+    /// Spawn a normal obstacle at exactly `rixel`, bypassing the RNG. It is telegraphed like any
+    /// other obstacle.
+    pub fn spawn_obstacle_at(
+        &mut self,
+        rixel: RixelCoord,
+        speed: f32,
+        width: f32,
+        length: f32,
+    ) -> Result<()> {
+        let side = Obstacle::rixel_to_direction(rixel)?;
+        self.push_obstacle(Obstacle {
+            id: self.next_obstacle_id,
+            rixel,
+            side,
+            speed,
+            acceleration: 0.,
+            width,
+            length,
+            lifetime: -(OBSTACLE_PRE_SPAWN_WARN_TIME as f32),
+            kind: ObstacleKind::Normal,
+            touched_player: false,
+            grazed: false,
+        });
+        Ok(())
+    }
+
+    /// Put a freshly spawned obstacle on the field and log it.
+    fn push_obstacle(&mut self, ob: Obstacle) {
+        self.next_obstacle_id = ob.id + 1;
+        self.obstacle_events.push(ObstacleEvent::Spawned {
+            id: ob.id,
+            rixel: ob.rixel.0,
+            speed: ob.speed,
+            width: ob.width,
+            tick: self.tick_count,
+        });
+        self.game_events.push(GameEventKind::ObstacleSpawned {
+            rixel: ob.rixel.0,
+            speed: ob.speed,
+        });
+        self.obstacles.push(ob);
+    }
+
+    /// Whether a boss obstacle is anywhere on or around the field.
+    fn boss_on_field(&self) -> bool {
+        self.obstacles.iter().any(|ob| ob.kind == ObstacleKind::Boss)
+    }
+
+    /// How many obstacles are on the field from each side, indexed by `Direction as usize`.
+    /// Obstacles that are still being telegraphed or have left don't count, and neither does one
+    /// whose rixel doesn't fall on any side.
+    fn active_obstacle_count_by_direction(&self) -> [usize; 4] {
+        let mut counts = [0; 4];
+        let active = self
+            .obstacles
+            .iter()
+            .filter(|ob| ob.phase() == ObstaclePhase::Active);
+        for ob in active {
+            if let Ok(side) = Obstacle::rixel_to_direction(ob.rixel) {
+                counts[side as usize] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Deal with whatever went wrong in an update or draw. Only quicksilver's own errors (e.g. a
+    /// lost context) are fatal; anything else goes to the error screen. Either way it's written
+    /// to `CRASH_LOG_PATH` first.
+    fn handle_error(&mut self, result: Result<()>) -> quicksilver::Result<()> {
+        let e = match result {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        let fatal = matches!(e, Error::Quicksilver(_));
+        let report = CrashReport {
+            error: &e,
+            fatal,
+            time: unix_time(),
+            tick: self.tick_count,
+            recent_fps: self.fps_graph.recent_average_fps(),
+            obstacles: self.obstacles.len(),
+        };
+        // Failing to write the log mustn't make things any worse.
+        if let Err(log_error) = report.write() {
+            eprintln!("Failed to write {}: {}", CRASH_LOG_PATH, log_error);
+        }
+
+        match e {
+            Error::Quicksilver(e) => Err(e),
+            e => {
+                self.enter_error_screen(e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Switch to the error screen for `e`. Anything in flight is dropped, since it may be what
+    /// caused the error and the error screen shouldn't trip over it again.
+    fn enter_error_screen(&mut self, e: Error) {
+        eprintln!("Error: {}", e);
+        self.obstacles.clear();
+        self.ripples.clear();
+        self.exit_sweeps.clear();
+        self.popups.clear();
+        self.reset_countdown = None;
+        self.paused = false;
+        self.resume_countdown = None;
+        self.quit_prompt = false;
+        self.error = Some(e);
+        self.screen = Screen::Error;
+    }
+
+    /// Capture the resumable part of the game. Returns `None` outside of a run, during the
+    /// tutorial, or if the player is mid-death, since there is nothing worth resuming.
+    fn snapshot(&self) -> Option<GameSnapshot> {
+        if self.screen != Screen::Playing
+            || self.tutorial.is_some()
+            || self.reset_countdown.is_some()
+        {
+            return None;
+        }
+
+        Some(GameSnapshot {
+            saved_at: unix_time(),
+            obstacles: self.obstacles.clone(),
+            player: self.player.clone(),
+            spawn_interval: self.spawn_interval,
+            since_last_spawn: self.last_spawned.map(|t| self.clock.since(t)),
+            difficulty: self.difficulty,
+            mode: self.mode,
+            seed: self.seed,
+        })
+    }
+
+    fn restore(&mut self, snapshot: GameSnapshot) {
+        self.screen = Screen::Playing;
+        self.difficulty = snapshot.difficulty;
+        self.mode = snapshot.mode;
+        self.seed = snapshot.seed;
+        // The RNG state isn't saved, so a restored run can't be replayed.
+        self.recorder = None;
+        self.playback = None;
+        self.obstacles = snapshot.obstacles;
+        self.next_obstacle_id = self.obstacles.iter().map(|ob| ob.id + 1).max().unwrap_or(0);
+        let skin = self.player.skin;
+        self.player = snapshot.player;
+        self.player.skin = skin;
+        self.displayed_score = self.player.score as f32;
+        // Don't send in a boss for a milestone the player already passed before saving.
+        self.last_boss_score = self.player.score / BOSS_SCORE_INTERVAL * BOSS_SCORE_INTERVAL;
+        self.last_magnet_score = self.player.score / MAGNET_SCORE_INTERVAL * MAGNET_SCORE_INTERVAL;
+        self.spawn_interval = snapshot.spawn_interval;
+        let now = self.clock.now();
+        self.last_spawned = snapshot
+            .since_last_spawn
+            .map(|d| now - d.as_secs_f64());
+    }
+
+    fn start_resume_countdown(&mut self) {
+        self.resume_countdown = Some(Countdown::new(
+            &self.ui_clock,
+            Duration::from_millis(RESUME_COUNTDOWN_MS),
+        ));
+    }
+
+    /// Save what should outlive the process, right before it exits.
+    fn shut_down(&self) {
+        self.save_snapshot();
+        if self.frame_spikes.iter().next().is_some() {
+            println!("Frame spikes this session:");
+            for spike in self.frame_spikes.iter() {
+                println!("  {}", spike);
+            }
+        }
+    }
+
+    /// Write the current snapshot to disk. An empty snapshot is written when there is nothing to
+    /// resume, so a stale one doesn't get picked up on the next launch. Deterministic builds never
+    /// restore a snapshot, since whether one is fresh depends on the wall clock, so they don't save
+    /// one either.
+    fn save_snapshot(&self) {
+        if cfg!(feature = "deterministic") {
+            return;
+        }
+        if let Err(e) = saving::save(APP_NAME, SNAPSHOT_PROFILE, &self.snapshot()) {
+            eprintln!("Failed to save snapshot: {}", e);
+        }
+    }
+}
+
+// Drawing logic.
+impl GameState {
+    fn draw_obstacles(&self) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        // Draw the obstacle warnings, including the ones left by obstacles that have gone.
+        let exit_sweeps: &[ExitSweep] = match self.quality {
+            Quality::Full => &self.exit_sweeps,
+            Quality::Reduced => &[],
+        };
+        // Faster obstacles get redder, thicker warnings, so the urgent lanes stand out.
+        let settings = self.difficulty.settings();
+        let warnings = self
+            .obstacles
+            .iter()
+            .flat_map(|ob| {
+                let urgency = ob.urgency(&settings);
+                let color = ob
+                    .kind
+                    .warning_color(&self.scheme)
+                    .lerp(&Color::RED, urgency);
+                let width = OBSTACLE_WARNING_WIDTH + OBSTACLE_WARNING_URGENT_EXTRA_WIDTH * urgency;
+                ob.warning_rects(width).map(move |rect| (rect, color))
+            })
+            .chain(
+                exit_sweeps
+                    .iter()
+                    .flat_map(ExitSweep::warning_rects)
+                    .map(|rect| (rect, self.scheme.warning)),
+            );
+        for (rect, color) in warnings {
+            commands.push(DrawCommand::new(rect.on_playfield(), color, Z_OBSTACLES));
+        }
+
+        // Then draw the obstacles themselves, with the ones about to leave on top.
+        let mut obstacles: Vec<&Obstacle> = self.obstacles.iter().collect();
+        obstacles.sort_by(|a, b| a.draw_order(b));
+        for obstacle in obstacles {
+            let mut base = obstacle.kind.color(&self.scheme);
+            if obstacle.kind == ObstacleKind::Boss && self.quality == Quality::Full {
+                base = base.strobe(
+                    &Duration::from_secs_f64(self.clock.now()),
+                    Duration::from_millis(BOSS_STROBE_PERIOD_MS),
+                );
+            }
+            // Whatever hit the player strobes. It runs on game time rather than the reset
+            // countdown, so another hit doesn't restart it, offset by id so overlapping obstacles
+            // are out of step.
+            let hit = obstacle.kind.is_lethal()
+                && obstacle
+                    .hitbox()
+                    .is_some_and(|rect| self.player.overlaps(&self.field, &rect));
+            let color = if self.reset_countdown.is_some() && hit {
+                let offset = Duration::from_millis(obstacle.id * HIT_STROBE_OFFSET_MS);
+                base.strobe(
+                    &(Duration::from_secs_f64(self.clock.now()) + offset),
+                    Duration::from_millis(HIT_STROBE_PERIOD_MS),
+                )
+            } else {
+                base
+            };
+            commands.push(DrawCommand::new(
+                obstacle.rectangle().on_playfield(),
+                color,
+                Z_OBSTACLES,
+            ));
+            if let Some(edge) = obstacle.leading_edge() {
+                commands.push(DrawCommand::new(
+                    edge.on_playfield(),
+                    lerp_color(color, Color::WHITE, 0.6),
+                    Z_OBSTACLES,
+                ));
+            }
+        }
+
+        commands
+    }
+
+    /// Shade each cell of the field from green to red by how many lethal obstacles the player
+    /// would be hit by if centered on it.
+    fn draw_danger_map(&self) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        if !self.danger_map {
+            return commands;
+        }
+
+        let size = self.player.rect.size;
+        let cells_x = (self.field.width / DANGER_MAP_CELL_SIZE) as usize;
+        let cells_y = (self.field.height / DANGER_MAP_CELL_SIZE) as usize;
+        for y in 0..cells_y {
+            for x in 0..cells_x {
+                let cell = Rectangle::new(
+                    (x as f32 * DANGER_MAP_CELL_SIZE, y as f32 * DANGER_MAP_CELL_SIZE),
+                    (DANGER_MAP_CELL_SIZE, DANGER_MAP_CELL_SIZE),
+                );
+                let pos = cell.center() - size * 0.5;
+                let hits = self
+                    .obstacles
+                    .iter()
+                    .filter(|ob| ob.kind.is_lethal() && ob.would_collide_with_player_at(pos, size))
+                    .count();
+                let danger = hits as f32 / DANGER_MAP_SATURATION as f32;
+                commands.push(DrawCommand::new(
+                    cell.on_playfield(),
+                    lerp_color(Color::GREEN, Color::RED, danger).with_alpha(DANGER_MAP_ALPHA),
+                    Z_DANGER_MAP,
+                ));
+            }
+        }
+
+        commands
+    }
+
+    fn draw_popups(&mut self, window: &mut Window) -> Result<()> {
+        let clock = &self.clock;
+        let hud = self.scheme.hud;
+        let popups = &self.popups;
+        self.font.execute(|font| {
+            for popup in popups {
+                let progress = popup.progress(clock).min(1.);
+                let style = FontStyle::new(FONT_SIZE_PT, hud.with_alpha(1. - progress));
+                let img = font.render(&popup.text, &style)?;
+                let size = img.area().size();
+                let rise = SCORE_POPUP_RISE * progress;
+                let pos = popup.pos - Vector::new(size.x / 2., size.y / 2. + rise);
+                window.draw(
+                    &Rectangle::new(pos, size).on_playfield(),
+                    Background::Img(&img),
+                );
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// A faint grid to help judge distances, and a vignette that slowly brightens and dims toward
+    /// the edges of the field.
+    fn draw_backdrop(&self) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        let grid = self.scheme.field_border.with_alpha(FIELD_GRID_ALPHA);
+        for line in &self.field_grid {
+            commands.push(DrawCommand::new(line.on_playfield(), grid, Z_FIELD));
+        }
+
+        let period = VIGNETTE_PULSE_PERIOD_MS as f64 / 1000.;
+        let pulse =
+            ((self.clock.now() / period * std::f64::consts::PI * 2.).sin() * 0.5 + 0.5) as f32;
+        let alpha = VIGNETTE_MIN_ALPHA.lerp(&VIGNETTE_MAX_ALPHA, pulse);
+        for band in 0..VIGNETTE_BANDS {
+            let fade = band as f32 / VIGNETTE_BANDS as f32;
+            let color = self.scheme.field_border.with_alpha(alpha.lerp(&0., fade));
+            let inset = band as f32 * VIGNETTE_BAND_WIDTH;
+            for rect in &self.field.frame(inset, VIGNETTE_BAND_WIDTH) {
+                commands.push(DrawCommand::new(rect.on_playfield(), color, Z_FIELD));
+            }
+        }
+
+        commands
+    }
+
+    fn draw_field_border(&self) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        // How far through the bomb flash we are, from 0 to 1. 1 means no flash.
+        let flash = self.bomb_flash.map_or(1., |t| {
+            (self.clock.since(t).as_millis() as f32 / BOMB_FLASH_DURATION_MS as f32).min(1.)
+        });
+
+        commands.push(DrawCommand::new(
+            Rectangle::new(
+                (-FIELD_EDGE_BORDER_WIDTH, -FIELD_EDGE_BORDER_WIDTH),
+                (
+                    FIELD_EDGE_BORDER_WIDTH * 2. + self.field.width,
+                    FIELD_EDGE_BORDER_WIDTH * 2. + self.field.height,
+                ),
+            )
+            .on_playfield(),
+            lerp_color(Color::WHITE, self.scheme.field_border, flash),
+            Z_FIELD,
+        ));
+
+        commands.push(DrawCommand::new(
+            self.field.rect().on_playfield(),
+            self.scheme.background,
+            Z_FIELD,
+        ));
+
+        let full_quality = self.quality == Quality::Full;
+        if self.show_backdrop && full_quality {
+            commands.extend(self.draw_backdrop());
+        }
+
+        let mut safe_zone_color = self.scheme.field_border.with_alpha(SAFE_ZONE_ALPHA);
+        if full_quality {
+            safe_zone_color = safe_zone_color.strobe_alpha(
+                &Duration::from_secs_f64(self.clock.now()),
+                Duration::from_millis(SAFE_ZONE_PULSE_PERIOD_MS),
+                SAFE_ZONE_MIN_ALPHA,
+            );
+        }
+        commands.push(DrawCommand::new(
+            util::compute_safe_zone(&self.field, &self.obstacles).on_playfield(),
+            safe_zone_color,
+            Z_FIELD,
+        ));
+
+        if flash < 1. {
+            commands.push(DrawCommand::new(
+                self.field.rect().on_playfield(),
+                Color::WHITE.with_alpha((1. - flash) * 0.5),
+                Z_FIELD,
+            ));
+        }
+
+        let ripples: &[BorderRipple] = if full_quality { &self.ripples } else { &[] };
+        for ripple in ripples {
+            let progress = ripple.progress(&self.clock).min(1.);
+            let growth = BORDER_RIPPLE_START_GROWTH.lerp(&BORDER_RIPPLE_END_GROWTH, progress);
+            // Centered on the border line.
+            let rect = Obstacle::side_rectangle(
+                ripple.side,
+                ripple.rixel,
+                BORDER_RIPPLE_THICKNESS / 2.,
+                BORDER_RIPPLE_THICKNESS,
+                ripple.width + growth,
+            );
+            commands.push(DrawCommand::new(
+                rect.on_playfield(),
+                self.scheme.field_border.with_alpha(1. - progress),
+                Z_FIELD,
+            ));
+        }
+
+        commands
+    }
+
+    fn draw_hud(&mut self, window: &mut Window) -> Result<()> {
+        let style = &self.font_style;
+        let mut hud = HudStack::new(Display::current().size(), HUD_CORNER_PADDING);
+        // Average and 1% low, since the average alone hides stutter.
+        if let Some((average, low)) = self.fps_readout {
+            // Red for a moment after a frame spike.
+            let spiked = matches!(self.last_frame_spike, Some(t)
+                if self.ui_clock.since(t) < Duration::from_millis(FRAME_SPIKE_HIGHLIGHT_MS));
+            let spike_style = FontStyle::new(FONT_SIZE_PT, Color::RED);
+            let style = if spiked { &spike_style } else { style };
+            self.font.execute(|font| {
+                let img = font.render(&format!("{:.0} / {:.0}", average, low), style)?;
+                window.draw(
+                    &hud.place(HudAnchor::TopLeft, img.area().size()),
+                    Background::Img(&img),
+                );
+                Ok(())
+            })?;
+        }
+
+        // Which day a daily run is for, and the best anyone here has done on it, go under the FPS.
+        if self.mode == Mode::Daily {
+            let date = format_date(self.seed.saturating_mul(SECS_PER_DAY));
+            let mut lines = vec![format!("Daily {} (seed {})", date, self.seed)];
+            if let Some(best) = self.high_scores.daily_best(self.seed) {
+                lines.push(format!("Day's best {}", format_score(best)));
+            }
+            self.font.execute(|font| {
+                for line in &lines {
+                    let img = font.render(line, style)?;
+                    window.draw(
+                        &hud.place(HudAnchor::TopLeft, img.area().size()),
+                        Background::Img(&img),
+                    );
+                }
+                Ok(())
+            })?;
+        }
+
+        // How long the run has lasted goes along the top, stopping once the player is hit.
+        let survived = format_clock(self.run_stats.survived());
+        self.font.execute(|font| {
+            let img = font.render(&survived, style)?;
+            window.draw(
+                &hud.place(HudAnchor::TopCenter, img.area().size()),
+                Background::Img(&img),
+            );
+            Ok(())
+        })?;
+
+        // Announce a boss along the top while one is around.
+        let boss = self.obstacles.iter().find(|ob| ob.kind == ObstacleKind::Boss);
+        if let Some(boss) = boss {
+            let text = match boss.phase() {
+                ObstaclePhase::Warning => "Boss incoming!",
+                ObstaclePhase::Active | ObstaclePhase::Exiting => "Survive the boss!",
+            };
+            self.font.execute(|font| {
+                let img = font.render(text, style)?;
+                window.draw(
+                    &hud.place(HudAnchor::TopCenter, img.area().size()),
+                    Background::Img(&img),
+                );
+                Ok(())
+            })?;
+        }
+
+        // Score and the player's resources go down the top-right corner.
+        let mut lines = vec![
+            format_score(self.displayed_score.round() as u32),
+            format!("Bombs {}", self.player.bombs),
+        ];
+        if self.difficulty.settings().player_lives > 0 {
+            lines.push(format!("Lives {}", self.player.lives));
+        }
+        if self.is_slowmo {
+            lines.push("[SLOW]".to_string());
+        }
+        // The score turns orange while camping pressure is draining it.
+        let camping_style = FontStyle::new(FONT_SIZE_PT, Color::ORANGE);
+        let camping = self.is_camping;
+        self.font.execute(|font| {
+            for (i, line) in lines.iter().enumerate() {
+                let style = if i == 0 && camping {
+                    &camping_style
+                } else {
+                    style
+                };
+                let img = font.render(line, style)?;
+                window.draw(
+                    &hud.place(HudAnchor::TopRight, img.area().size()),
+                    Background::Img(&img),
+                );
+            }
+            Ok(())
+        })?;
+
+        // Session bests go in the bottom-left corner once there's a finished run to report.
+        if self.session_stats.runs > 0 {
+            let lines = self.session_stats.lines();
+            self.font.execute(|font| {
+                for line in lines.iter().rev() {
+                    let img = font.render(line, style)?;
+                    window.draw(
+                        &hud.place(HudAnchor::BottomLeft, img.area().size()),
+                        Background::Img(&img),
+                    );
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_screen_flash(&self, window: &mut Window) -> Result<()> {
+        if let Some(flash) = &self.screen_flash {
+            window.draw(
+                &self.field.rect().on_playfield(),
+                Background::Col(flash.current_color(&self.clock)),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Show how long is left before the game resets after the player is hit.
+    fn draw_reset_countdown(&mut self, window: &mut Window) -> Result<()> {
+        let remaining = match &self.reset_countdown {
+            Some(c) => c.fraction_remaining(&self.clock),
+            None => return Ok(()),
+        };
+
+        // The bar is centered under the field and empties as the countdown runs.
+        let top = self.field.height + FIELD_EDGE_BORDER_WIDTH + RESET_BAR_GAP;
+        let left = (self.field.width - RESET_BAR_WIDTH) / 2.;
+        window.draw(
+            &Rectangle::new((left, top), (RESET_BAR_WIDTH, RESET_BAR_HEIGHT)).on_playfield(),
+            Background::Col(self.scheme.hud.with_alpha(0.3)),
+        );
+        window.draw(
+            &Rectangle::new((left, top), (RESET_BAR_WIDTH * remaining, RESET_BAR_HEIGHT))
+                .on_playfield(),
+            Background::Col(self.scheme.hud),
+        );
+
+        let text_center = Rectangle::new((0., top + RESET_BAR_HEIGHT), (self.field.width, 0.))
+            .on_playfield()
+            .center()
+            + Vector::new(0., FONT_SIZE_PT);
+        draw_text_lines(
+            window,
+            &mut self.font,
+            &self.font_style,
+            &["Resetting...".to_string()],
+            text_center,
+        )
+    }
+
+    /// Show why the simulation is frozen, if it is.
+    fn draw_pause(&mut self, window: &mut Window) -> Result<()> {
+        if !self.paused {
+            return Ok(());
+        }
+
+        let text = match &self.resume_countdown {
+            Some(_) => "Resuming...",
+            None => "Paused",
+        };
+        draw_text_lines(
+            window,
+            &mut self.font,
+            &self.font_style,
+            &[text.to_string()],
+            Display::current().size() / 2.,
+        )
+    }
+
+    fn draw_quit_prompt(&mut self, window: &mut Window) -> Result<()> {
+        if !self.quit_prompt {
+            return Ok(());
+        }
+
+        draw_dimmer(window);
+        draw_text_lines(
+            window,
+            &mut self.font,
+            &self.font_style,
+            &["Quit? Y/N".to_string()],
+            Display::current().size() / 2.,
+        )
+    }
+
+    fn draw_title(&mut self, window: &mut Window) -> Result<()> {
+        if self.show_high_scores {
+            return self.draw_high_scores(window);
+        }
+        let lines = [
+            GAME_TITLE.to_string(),
+            format!("Difficulty: < {} >", self.difficulty.name()),
+            format!("Mode: {}", self.mode.name()),
+            "Press Enter to start, H for high scores".to_string(),
+        ];
+        draw_text_lines(
+            window,
+            &mut self.font,
+            &self.font_style,
+            &lines,
+            Display::current().size() / 2.,
+        )
+    }
+
+    fn draw_game_over(&mut self, window: &mut Window) -> Result<()> {
+        if self.show_high_scores && self.initials_entry.is_none() {
+            return self.draw_high_scores(window);
+        }
+        let mut lines = vec![
+            "Game over".to_string(),
+            format!("Score: {}", format_score(self.player.score)),
+        ];
+        lines.extend(self.run_stats.lines());
+        match &self.initials_entry {
+            Some(initials_entry) => lines.extend(initials_entry.lines()),
+            None => {
+                lines.push("Enter to play again, Escape for title, H for high scores".to_string())
+            }
+        }
+        draw_text_lines(
+            window,
+            &mut self.font,
+            &self.font_style,
+            &lines,
+            Display::current().size() / 2.,
+        )
+    }
+
+    fn draw_high_scores(&mut self, window: &mut Window) -> Result<()> {
+        let mut lines = vec!["High scores".to_string()];
+        lines.extend(self.high_scores.lines());
+        lines.push("H to go back".to_string());
+        draw_text_lines(
+            window,
+            &mut self.font,
+            &self.font_style,
+            &lines,
+            Display::current().size() / 2.,
+        )
+    }
+
+    fn draw_error(&mut self, window: &mut Window) -> Result<()> {
+        let lines = [
+            "Something went wrong".to_string(),
+            self.error.as_ref().map_or_else(String::new, |e| e.to_string()),
+            "R to restart, Escape to quit".to_string(),
+        ];
+        draw_text_lines(
+            window,
+            &mut self.font,
+            &self.font_style,
+            &lines,
+            Display::current().size() / 2.,
+        )
+    }
+
+    fn draw_debug(&mut self, window: &mut Window) -> Result<()> {
+        if !self.debug {
+            return Ok(());
+        }
+
+        let [north, east, south, west] = self.active_obstacle_count_by_direction();
+        let lines = [
+            format!(
+                "Obstacles {}/{}",
+                self.obstacles.len(),
+                self.difficulty.settings().max_obstacles
+            ),
+            format!("Peak obstacles {}", self.peak_obstacles.max().unwrap_or(0)),
+            format!("N {} E {} S {} W {}", north, east, south, west),
+            format!("Spawn every {}ms", self.spawn_interval_millis()),
+            format!(
+                "FPS min {:.0} max {:.0}",
+                self.fps_graph.min().unwrap_or(0.),
+                self.fps_graph.max().unwrap_or(0.)
+            ),
+            format!("Quality {:?}", self.quality),
+        ];
+        // The latest frames' timings under the rest, newest at the bottom.
+        let mut frames: Vec<String> = self
+            .diagnostics
+            .last(DIAGNOSTICS_SHOWN_FRAMES)
+            .map(|frame| {
+                format!(
+                    "#{} upd {}us draw {}us ob {} fx {}",
+                    frame.frame_id,
+                    frame.update_duration_us,
+                    frame.draw_duration_us,
+                    frame.obstacle_count,
+                    frame.active_particles
+                )
+            })
+            .collect();
+        frames.reverse();
+        let style = &self.font_style;
+        let mut hud = HudStack::new(Display::current().size(), HUD_CORNER_PADDING);
+        self.font.execute(|font| {
+            for line in lines.iter().chain(&frames).rev() {
+                let img = font.render(line, style)?;
+                window.draw(
+                    &hud.place(HudAnchor::BottomRight, img.area().size()),
+                    Background::Img(&img),
+                );
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Label every obstacle at the edge it enters from with its rixel and how far around the border
+    /// that is, rule the field into quarters, and draw the obstacle heatmap if it is on.
+    #[cfg(feature = "debug")]
+    fn draw_debug_overlay(&mut self, window: &mut Window) -> Result<()> {
+        let rule = Background::Col(self.scheme.warning.with_alpha(0.5));
+        window.draw(
+            &Rectangle::new((0., FIELD_HEIGHT / 2.), (FIELD_WIDTH, 1.)).on_playfield(),
+            rule,
+        );
+        window.draw(
+            &Rectangle::new((FIELD_WIDTH / 2., 0.), (1., FIELD_HEIGHT)).on_playfield(),
+            rule,
+        );
+
+        // Heat is shown relative to the hottest cell.
+        if let Some(heatmap) = &self.heatmap {
+            let hottest = heatmap.iter().flatten().cloned().max().unwrap_or(0).max(1);
+            let cell = Vector::new(
+                self.field.width / HEATMAP_GRID_SIZE as f32,
+                self.field.height / HEATMAP_GRID_SIZE as f32,
+            );
+            for (y, row) in heatmap.iter().enumerate() {
+                for (x, &heat) in row.iter().enumerate() {
+                    if heat == 0 {
+                        continue;
+                    }
+                    let alpha = heat as f32 / hottest as f32 * HEATMAP_MAX_ALPHA;
+                    window.draw(
+                        &Rectangle::new((x as f32 * cell.x, y as f32 * cell.y), cell)
+                            .on_playfield(),
+                        Background::Col(Color::RED.with_alpha(alpha)),
+                    );
+                }
+            }
+        }
+
+        let mut labels = Vec::with_capacity(self.obstacles.len());
+        for ob in &self.obstacles {
+            let entry = Obstacle::side_rectangle(ob.side, ob.rixel, 0., 0., 0.).on_playfield();
+            let label = format!(
+                "{:.1} ({:.2}) speed {:.2}",
+                ob.rixel.0,
+                ob.rixel_normalized(),
+                ob.speed
+            );
+            labels.push((label, entry.pos));
+        }
+        let style = FontStyle::new(DEBUG_FONT_SIZE_PT, self.scheme.hud);
+        self.font.execute(|font| {
+            for (label, pos) in &labels {
+                let img = font.render(label, &style)?;
+                window.draw(
+                    &Rectangle::new(*pos - img.area().size() * 0.5, img.area().size()),
+                    Background::Img(&img),
+                );
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    fn draw_player(&self) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        // Fades from 1 to 0 over a graze flash.
+        let flash = self
+            .player
+            .graze_flash
+            .as_ref()
+            .map_or(0., |flash| flash.fraction_remaining(&self.clock));
+        let (player, collector) = match self.player.skin {
+            Some(skin) => (skin.player, skin.collector),
+            None => (self.scheme.player, self.scheme.collector),
+        };
+        let collector_color = if flash > 0. {
+            collector.lerp(&Color::WHITE, flash)
+        } else if let Some(magnet) = &self.player.magnet {
+            let faded = collector.with_alpha(MAGNET_COLLECTOR_ALPHA);
+            let remaining = magnet.remaining(&self.clock);
+            if remaining < Duration::from_millis(MAGNET_EXPIRY_BLINK_MS) {
+                faded.checkerboard(
+                    &collector,
+                    &remaining,
+                    Duration::from_millis(MAGNET_EXPIRY_BLINK_RATE_MS),
+                )
+            } else {
+                faded
+            }
+        } else {
+            collector
+        };
+        commands.push(DrawCommand::new(
+            self.player.collector_rectangle().on_playfield(),
+            collector_color,
+            Z_PLAYER,
+        ));
+        // Outline the player by drawing a slightly bigger rectangle behind it.
+        let border_color = if self.is_slowmo {
+            Color::CYAN
+        } else {
+            self.scheme.field_border
+        };
+        // Strobes once the player has been hit, like whatever hit them.
+        let player_color = match &self.reset_countdown {
+            Some(countdown) => {
+                player.strobe(&countdown.elapsed(&self.clock), Duration::from_millis(500))
+            }
+            None => player.lerp(&self.scheme.highlight, flash),
+        };
+        // Each older position is drawn smaller and fainter than the one after it.
+        let trail_shown = match self.quality {
+            Quality::Full => PLAYER_TRAIL_LENGTH,
+            Quality::Reduced => 0,
+        };
+        for (age, center) in self.player.trail.iter().rev().take(trail_shown).enumerate() {
+            let fade = 1. - PLAYER_TRAIL_ALPHA_STEP * (age + 1) as f32;
+            if fade <= 0. {
+                break;
+            }
+            commands.push(DrawCommand::new(
+                Rectangle::new_sized(self.player.rect.size * fade)
+                    .with_center(*center)
+                    .on_playfield(),
+                player_color.with_alpha(player_color.a * fade),
+                Z_PLAYER,
+            ));
+        }
+        if let Some(target) = self.touch_target {
+            commands.push(DrawCommand::new(
+                Rectangle::new_sized((TOUCH_TARGET_SIZE, TOUCH_TARGET_SIZE))
+                    .with_center(target)
+                    .on_playfield(),
+                self.scheme.highlight.with_alpha(TOUCH_TARGET_ALPHA),
+                Z_PLAYER,
+            ));
+        }
+        for rect in self.player.rects(&self.field) {
+            commands.push(DrawCommand::new(
+                Rectangle::new(
+                    rect.pos - Vector::new(PLAYER_BORDER_WIDTH, PLAYER_BORDER_WIDTH),
+                    rect.size + Vector::new(PLAYER_BORDER_WIDTH, PLAYER_BORDER_WIDTH) * 2.,
+                )
+                .on_playfield(),
+                border_color,
+                Z_PLAYER,
+            ));
+
+            commands.push(DrawCommand::new(
+                rect.on_playfield(),
+                player_color,
+                Z_PLAYER,
+            ));
+        }
+
+        let bar = &self.player.rect;
+        commands.push(DrawCommand::new(
+            Rectangle::new(
+                (bar.x(), bar.y() + bar.height() + STAMINA_BAR_GAP),
+                (bar.width() * self.player.stamina, STAMINA_BAR_HEIGHT),
+            )
+            .on_playfield(),
+            lerp_color(Color::RED, Color::GREEN, self.player.stamina),
+            Z_PLAYER,
+        ));
+
+        commands
+    }
+}
+
+// Update logic
+impl GameState {
+    /// Advance a run by one tick of `dt` with the player's `input`. Everything that affects the
+    /// simulation goes through here, so it can run without a window.
+    fn step(&mut self, dt: Duration, input: InputSnapshot) -> Result<()> {
+        self.clock.advance(dt);
+
+        self.update_handle_input(input)?;
+        self.update_camping_pressure(dt);
+        self.update_check_collisions(dt)?;
+        self.update_spawn_obstacles()?;
+        self.update_spawn_boss()?;
+        self.update_despawn_obstacles()?;
+        self.update_survival_milestones();
+        self.process_events();
+        self.update_effects()?;
+        self.update_displayed_score(dt);
+        self.update_score_milestones()?;
+        self.update_magnet();
+        self.update_tutorial()?;
+        self.update_reset_game()?;
+        #[cfg(feature = "debug")]
+        self.update_heatmap();
+
+        Ok(())
+    }
+
+    /// Add a tick of heat to every heatmap cell an obstacle is covering.
+    #[cfg(feature = "debug")]
+    fn update_heatmap(&mut self) {
+        let heatmap = match &mut self.heatmap {
+            Some(heatmap) => heatmap,
+            None => return,
+        };
+        let cell = Vector::new(
+            self.field.width / HEATMAP_GRID_SIZE as f32,
+            self.field.height / HEATMAP_GRID_SIZE as f32,
+        );
+        // Cells from the one containing `start` up to but not including the one containing `end`.
+        let cells = |start: f32, end: f32, size: f32| {
+            let first = (start / size).floor().max(0.) as usize;
+            let last = ((end / size).ceil().max(0.) as usize).min(HEATMAP_GRID_SIZE);
+            first..last
+        };
+        for ob in &self.obstacles {
+            let rect = match ob.hitbox() {
+                Some(rect) => rect,
+                None => continue,
+            };
+            for y in cells(rect.y(), rect.y() + rect.height(), cell.y) {
+                for x in cells(rect.x(), rect.x() + rect.width(), cell.x) {
+                    heatmap[y][x] += 1;
+                }
+            }
+        }
+    }
+
+    fn update_handle_input(&mut self, input: InputSnapshot) -> Result<()> {
+        // During a replay the recorded input stands in for the player's.
+        let input = match &mut self.playback {
+            Some(playback) => playback.next_input(),
+            None => input,
+        };
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(input);
+        }
+        let InputSnapshot {
+            left,
+            down,
+            up,
+            right,
+            ..
+        } = input;
+
+        // Slow-mo only uses stamina while actually moving.
+        let slowmo = input.slowmo
+            && (left || down || up || right)
+            && self.reset_countdown.is_none()
+            && !self.player.exhausted;
+        self.is_slowmo = slowmo;
+        let (stamina, exhausted) =
+            util::step_stamina(self.player.stamina, self.player.exhausted, slowmo);
+        self.player.stamina = stamina;
+        self.player.exhausted = exhausted;
+
+        // Check movement. Only one direction counts at a time. Once the player has been hit, input
+        // is ignored and friction brings them to a stop.
+        let direction = if self.reset_countdown.is_some() {
+            Vector::ZERO
+        } else if left {
+            Vector::new(-1, 0)
+        } else if down {
+            Vector::new(0, 1)
+        } else if up {
+            Vector::new(0, -1)
+        } else if right {
+            Vector::new(1, 0)
+        } else {
+            Vector::ZERO
+        };
+        let movement = MovementInput { direction, slowmo };
+        let hit = apply_movement(
+            &movement,
+            &mut self.player,
+            &self.field,
+            1.,
+            self.wrap_edges,
+        );
+
+        // Stop them moving into whichever wall they hit so they don't stick to it.
+        if hit.x {
+            self.player.velocity.x = 0.;
+        }
+        if hit.y {
+            self.player.velocity.y = 0.;
+        }
+        self.player.record_trail();
+
+        if input.bomb && self.reset_countdown.is_none() {
+            self.detonate_bomb();
+        }
+
+        Ok(())
+    }
+
+    /// Keys that control the game rather than the player, so they aren't part of the recorded
+    /// input.
+    fn update_handle_keys(&mut self, keyboard: &Keyboard) -> Result<()> {
+        if keyboard[Key::F3] == ButtonState::Pressed {
+            self.debug = !self.debug;
+        }
+        if keyboard[Key::F4] == ButtonState::Pressed {
+            self.danger_map = !self.danger_map;
+        }
+        if keyboard[Key::F6] == ButtonState::Pressed {
+            self.cycle_skin();
+        }
+        #[cfg(feature = "debug")]
+        {
+            if keyboard[Key::F2] == ButtonState::Pressed {
+                self.heatmap = match self.heatmap {
+                    Some(_) => None,
+                    None => Some([[0; HEATMAP_GRID_SIZE]; HEATMAP_GRID_SIZE]),
+                };
+            }
+        }
+
+        // Drop an obstacle straight down onto the player. The spawn isn't part of the recorded
+        // input, so the run can no longer be replayed.
+        if self.debug && keyboard[Key::O] == ButtonState::Pressed {
+            let width = 10.;
+            let x = self
+                .player
+                .rect
+                .center()
+                .x
+                .clamp(width / 2., self.field.width - width / 2.);
+            let rixel = RixelCoord::try_from(PixelCoord(Vector::new(x, 0.)))?;
+            let speed = self.difficulty.settings().obstacle_speed(self.player.score);
+            self.recorder = None;
+            self.spawn_obstacle_at(rixel, speed, width, OBSTACLE_BASE_LENGTH)?;
+        }
+
+        // Send in one obstacle from each side at once. Like O, this stops the run being replayable.
+        if self.debug && keyboard[Key::W] == ButtonState::Pressed {
+            let wave = Obstacle::spawn_wave(
+                self.next_obstacle_id,
+                4,
+                &mut self.rng,
+                self.player.score,
+                &self.difficulty.settings(),
+            );
+            self.recorder = None;
+            for ob in wave {
+                self.push_obstacle(ob);
+            }
+        }
+
+        self.update_tutorial_keys(keyboard);
+
+        // Ask before quitting, so a stray press doesn't cost the run.
+        if keyboard[Key::Escape] == ButtonState::Pressed {
+            self.quit_prompt = true;
+        }
+
+        Ok(())
+    }
+
+    /// Y quits, which saves a snapshot so the run picks up again on the next launch. N or Escape
+    /// goes back to the run.
+    fn update_quit_prompt(&mut self, keyboard: &Keyboard) -> Result<()> {
+        if keyboard[Key::Y] == ButtonState::Pressed {
+            self.is_running = false;
+        } else if keyboard[Key::N] == ButtonState::Pressed
+            || keyboard[Key::Escape] == ButtonState::Pressed
+        {
+            self.quit_prompt = false;
+        }
+
+        Ok(())
+    }
+
+    fn update_title(&mut self, keyboard: &Keyboard) -> Result<()> {
+        if keyboard[Key::Left] == ButtonState::Pressed {
+            self.difficulty = util::cycle(&DifficultyPreset::ALL, self.difficulty, -1);
+        } else if keyboard[Key::Right] == ButtonState::Pressed {
+            self.difficulty = util::cycle(&DifficultyPreset::ALL, self.difficulty, 1);
+        }
+        if keyboard[Key::Up] == ButtonState::Pressed {
+            self.mode = util::cycle(&Mode::ALL, self.mode, -1);
+        } else if keyboard[Key::Down] == ButtonState::Pressed {
+            self.mode = util::cycle(&Mode::ALL, self.mode, 1);
+        }
+
+        if keyboard[Key::H] == ButtonState::Pressed {
+            self.show_high_scores = !self.show_high_scores;
+        }
+        if keyboard[Key::Return] == ButtonState::Pressed {
+            self.start_run();
+        } else if keyboard[Key::Escape] == ButtonState::Pressed {
+            if self.show_high_scores {
+                self.show_high_scores = false;
+            } else {
+                self.is_running = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn update_game_over(&mut self, keyboard: &Keyboard) -> Result<()> {
+        if let Some(initials_entry) = &mut self.initials_entry {
+            if let Some(entry) = initials_entry.update(keyboard) {
+                self.initials_entry = None;
+                self.record_high_score(entry);
+                self.show_high_scores = true;
+            }
+            return Ok(());
+        }
+
+        if keyboard[Key::H] == ButtonState::Pressed {
+            self.show_high_scores = !self.show_high_scores;
+        }
+        if keyboard[Key::Return] == ButtonState::Pressed {
+            self.start_run();
+        } else if keyboard[Key::Escape] == ButtonState::Pressed {
+            self.show_high_scores = false;
+            self.screen = Screen::Title;
+        }
+
+        Ok(())
+    }
+
+    fn update_error(&mut self, keyboard: &Keyboard) -> Result<()> {
+        if keyboard[Key::R] == ButtonState::Pressed {
+            self.error = None;
+            self.start_run();
+        } else if keyboard[Key::Escape] == ButtonState::Pressed {
+            self.is_running = false;
+        }
+
+        Ok(())
+    }
+
+    fn update_fps_graph(&mut self, window: &Window) -> Result<()> {
+        let fps = window.current_fps();
+        if let Some(average) = self.fps_graph.recent_average_fps() {
+            let frame_ms = 1000. / fps;
+            if fps.is_normal() && frame_ms > FRAME_SPIKE_FACTOR * 1000. / average {
+                let spike = FrameSpike {
+                    tick: self.tick_count,
+                    frame_ms,
+                };
+                eprintln!("Warning: frame spike {}", spike);
+                self.frame_spikes.push(spike);
+                self.last_frame_spike = Some(self.ui_clock.now());
+            }
+        }
+        self.fps_graph.log_fps(fps);
+        if let Some(average) = self.fps_graph.recent_average_fps() {
+            self.quality = match self.quality {
+                Quality::Full if average < REDUCED_QUALITY_FPS => Quality::Reduced,
+                Quality::Reduced if average > FULL_QUALITY_FPS => Quality::Full,
+                quality => quality,
+            };
+        }
+        self.update_fps_readout();
+
+        Ok(())
+    }
+
+    fn update_fps_readout(&mut self) {
+        if self.fps_update_tick.is_none()
+            || self.tick_count - self.fps_update_tick.unwrap() > FPS_UPDATE_INTERVAL_TICKS
+        {
+            self.fps_update_tick = Some(self.tick_count);
+            self.fps_readout = self
+                .fps_graph
+                .recent_average_fps()
+                .zip(self.fps_graph.percentile(1.));
+        }
+    }
+
+    /// Add every obstacle that has something in the field to `obstacle_grid`, keyed by its index.
+    fn fill_obstacle_grid(&mut self) {
+        for (i, ob) in self.obstacles.iter().enumerate() {
+            if let Some(rect) = ob.hitbox() {
+                self.obstacle_grid.insert(i, &rect);
+            }
+        }
+    }
+
+    /// Indices of the obstacles in `obstacle_grid` that overlap the player or the collector, in
+    /// ascending order. Only these need a closer look when checking collisions.
+    fn obstacles_near_player(&self) -> Vec<usize> {
+        let collector = self.player.collector_rectangle();
+        let mut near: Vec<usize> = self
+            .player
+            .rects(&self.field)
+            .iter()
+            .chain(std::iter::once(&collector))
+            .flat_map(|rect| self.obstacle_grid.query(rect))
+            .copied()
+            .collect();
+        near.sort_unstable();
+        near.dedup();
+        near
+    }
+
+    fn update_check_collisions(&mut self, dt: Duration) -> Result<()> {
+        // Obstacles keep moving during the death countdown; only collisions and scoring stop.
+        let resetting = self.reset_countdown.is_some();
+        let mut grazed = false;
+        let mut hit = false;
+        for ob in self.obstacles.iter_mut() {
+            ob.lifetime += 1.;
+            if ob.lifetime >= 0. && ob.lifetime < 1. {
+                self.ripples.push(BorderRipple {
+                    side: ob.side,
+                    rixel: ob.rixel,
+                    width: ob.width,
+                    birth: self.clock.now(),
+                });
+            }
+        }
+        // Nothing is hit while resetting.
+        self.obstacle_grid.clear();
+        if !resetting {
+            self.fill_obstacle_grid();
+        }
+
+        let collector = self.player.collector_rectangle();
+        for i in self.obstacles_near_player() {
+            let ob = &mut self.obstacles[i];
+            let rect = ob.rectangle();
+            if !ob.touched_player && self.player.overlaps(&self.field, &rect) {
+                ob.touched_player = true;
+                self.obstacle_events.push(ObstacleEvent::TouchedPlayer {
+                    id: ob.id,
+                    tick: self.tick_count,
+                });
+            }
+
+            // Check collisions. Non-lethal obstacles only count towards grazing, and nothing is
+            // lethal in practice mode or the tutorial.
+            if self.mode != Mode::Practice
+                && self.tutorial.is_none()
+                && ob.kind.is_lethal()
+                && self.player.overlaps(&self.field, &rect)
+            {
+                hit = true;
+            } else if collector.overlaps_rectangle(&rect) {
+                grazed = true;
+                self.game_events.push(GameEventKind::Grazed {
+                    kind: ob.kind,
+                    first: !ob.grazed,
+                });
+                ob.grazed = true;
+            }
+        }
+
+        if !resetting {
+            self.run_stats.log_tick(dt, self.obstacles.len(), grazed);
+            self.peak_obstacles.push(self.obstacles.len());
+            if hit {
+                self.game_events.push(GameEventKind::PlayerHit);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Queue a `SurvivalMilestone` each time the run's survival time passes another multiple of
+    /// `SURVIVAL_MILESTONE_SECS`. Survival time stops once the player has been hit.
+    fn update_survival_milestones(&mut self) {
+        let milestones = self.run_stats.survived().as_secs() / SURVIVAL_MILESTONE_SECS;
+        if milestones > self.survival_milestones {
+            self.survival_milestones = milestones;
+            self.game_events.push(GameEventKind::SurvivalMilestone {
+                seconds: milestones * SURVIVAL_MILESTONE_SECS,
+            });
+        }
+    }
+
+    /// Apply everything queued in `game_events` this tick, in the order it happened. Nothing
+    /// scores once the player has been hit.
+    fn process_events(&mut self) {
+        for event in std::mem::take(&mut self.game_events) {
+            if !matches!(event, GameEventKind::Grazed { first: false, .. }) {
+                self.event_log.push(GameEvent {
+                    tick: self.tick_count,
+                    kind: event,
+                });
+            }
+
+            let scoring = self.reset_countdown.is_none();
+            match event {
+                // Nothing reacts to spawns yet.
+                GameEventKind::ObstacleSpawned { .. } => {}
+                GameEventKind::PlayerHit if self.player.lives > 1 => {
+                    // Spend a life and clear whatever hit the player so it doesn't cost another.
+                    self.player.lives -= 1;
+                    let (player, field) = (&self.player, &self.field);
+                    self.obstacles.retain(|ob| {
+                        !(ob.kind.is_lethal()
+                            && ob.hitbox().is_some_and(|rect| player.overlaps(field, &rect)))
+                    });
+                }
+                GameEventKind::PlayerHit => {
+                    self.reset_countdown =
+                        Some(Countdown::new(&self.clock, Duration::from_secs(2)));
+                }
+                GameEventKind::ObstacleDespawned { kind, bonus } if scoring => {
+                    self.player.add_score(bonus);
+                    self.run_stats.log_dodge();
+                    if kind == ObstacleKind::Boss {
+                        self.popups.push(ScorePopup {
+                            text: format!("+{}", bonus),
+                            pos: self.field.center(),
+                            birth: self.clock.now(),
+                        });
+                    }
+                }
+                GameEventKind::Grazed { kind, first } if scoring => {
+                    self.player.add_score(kind.graze_points());
+                    if first {
+                        self.player.graze_flash = Some(Countdown::new(
+                            &self.clock,
+                            Duration::from_millis(GRAZE_FLASH_DURATION_MS),
+                        ));
+                    }
+                }
+                GameEventKind::ScoreChanged { points } if scoring => self.player.add_score(points),
+                GameEventKind::SurvivalMilestone { seconds } if scoring => {
+                    self.player.add_score(SURVIVAL_MILESTONE_POINTS);
+                    self.popups.push(ScorePopup {
+                        text: format!("{}s survived! +{}", seconds, SURVIVAL_MILESTONE_POINTS),
+                        pos: self.field.center(),
+                        birth: self.clock.now(),
+                    });
+                }
+                GameEventKind::ObstacleDespawned { .. }
+                | GameEventKind::Grazed { .. }
+                | GameEventKind::ScoreChanged { .. }
+                | GameEventKind::SurvivalMilestone { .. } => {}
+            }
+        }
+    }
+
+    /// How long the wait for the next obstacle is. Doubled while a boss is around, to let the
+    /// player focus on it.
+    fn effective_spawn_interval(&self) -> Duration {
+        if self.boss_on_field() {
+            self.spawn_interval * 2
+        } else {
+            self.spawn_interval
+        }
+    }
+
+    /// The interval between obstacle spawns in whole milliseconds. This is the effective
+    /// interval, so it is doubled while a boss is on the field.
+    pub fn spawn_interval_millis(&self) -> u64 {
+        u64::try_from(self.effective_spawn_interval().as_millis()).unwrap_or(u64::MAX)
+    }
+
+    fn update_spawn_obstacles(&mut self) -> Result<()> {
+        // The tutorial sends in its own obstacles.
+        if self.tutorial.is_some() {
+            return Ok(());
+        }
+        let interval = self.effective_spawn_interval();
+        if self.last_spawned.is_none() || self.clock.since(self.last_spawned.unwrap()) > interval {
+            self.last_spawned = Some(self.clock.now());
+            let settings = self.difficulty.settings();
+            // At the cap, skip this spawn but still wait a full interval before trying again.
+            if self.obstacles.len() >= settings.max_obstacles {
+                return Ok(());
+            }
+            let mut side_weights = settings.side_weights;
+            if settings.adaptive_sides {
+                let nearest = Direction::nearest(&self.player.rect, &self.field);
+                side_weights[nearest as usize] *= ADAPTIVE_SIDE_BIAS;
+            }
+            let side = Direction::random(&mut self.rng, &side_weights);
+            let ob = Obstacle::spawn(
+                self.next_obstacle_id,
+                side,
+                &mut self.rng,
+                self.player.score,
+                &settings,
+            );
+            self.push_obstacle(ob);
+            self.spawn_interval = settings.spawn_interval(self.player.score);
+        }
+
+        Ok(())
+    }
+
+    /// Send in a boss obstacle each time the score passes a multiple of `BOSS_SCORE_INTERVAL`.
+    /// Bosses ignore the obstacle cap.
+    fn update_spawn_boss(&mut self) -> Result<()> {
+        let milestone = self.player.score / BOSS_SCORE_INTERVAL * BOSS_SCORE_INTERVAL;
+        if milestone <= self.last_boss_score || self.reset_countdown.is_some() {
+            return Ok(());
+        }
+        self.last_boss_score = milestone;
+
+        let settings = self.difficulty.settings();
+        let side = Direction::random(&mut self.rng, &settings.side_weights);
+        let ob = Obstacle::spawn_boss(
+            self.next_obstacle_id,
+            side,
+            &mut self.rng,
+            self.player.score,
+            &settings,
+        );
+        self.push_obstacle(ob);
+
+        Ok(())
+    }
+
+    fn update_effects(&mut self) -> Result<()> {
+        let clock = &self.clock;
+        self.ripples.retain(|r| r.progress(clock) < 1.);
+        self.popups.retain(|p| p.progress(clock) < 1.);
+        for sweep in &mut self.exit_sweeps {
+            sweep.age += 1.;
+        }
+        self.exit_sweeps.retain(|s| !s.is_done());
+        if let Some(true) = self.screen_flash.as_ref().map(|f| f.is_done(clock)) {
+            self.screen_flash = None;
+        }
+
+        Ok(())
+    }
+
+    /// Move the HUD's score toward the real one, faster the further behind it is.
+    fn update_displayed_score(&mut self, dt: Duration) {
+        let target = self.player.score as f32;
+        let t = (dt.as_secs_f32() * SCORE_COUNT_RATE).min(1.);
+        self.displayed_score = self.displayed_score.lerp(&target, t);
+        if (target - self.displayed_score).abs() < 0.5 {
+            self.displayed_score = target;
+        }
+    }
+
+    /// Drain the score while the player camps in one spot, on difficulties that ask for it.
+    /// Nothing drains once the player has been hit or during the tutorial.
+    fn update_camping_pressure(&mut self, dt: Duration) {
+        self.player.record_position(&self.clock);
+        self.is_camping = self.difficulty.settings().camping_pressure
+            && self.reset_countdown.is_none()
+            && self.tutorial.is_none()
+            && util::is_camping(&self.player.position_history, self.clock.now());
+        if self.is_camping {
+            self.player.drain_score(dt);
+        }
+    }
+
+    /// Hand out a magnet each time the score passes a multiple of `MAGNET_SCORE_INTERVAL`, and
+    /// size the collector for whatever is left of it.
+    fn update_magnet(&mut self) {
+        let milestone = self.player.score / MAGNET_SCORE_INTERVAL * MAGNET_SCORE_INTERVAL;
+        if milestone > self.last_magnet_score {
+            self.last_magnet_score = milestone;
+            self.player.add_magnet(&self.clock);
+        }
+        self.player.update_collector(&self.clock);
+    }
+
+    fn update_score_milestones(&mut self) -> Result<()> {
+        let milestones = self.player.score / SCORE_MILESTONE_INTERVAL;
+        if milestones > self.milestones {
+            self.milestones = milestones;
+            self.trigger_flash(
+                self.scheme.hud.with_alpha(MILESTONE_FLASH_ALPHA),
+                Duration::from_millis(MILESTONE_FLASH_DURATION_MS),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn update_reset_game(&mut self) -> Result<()> {
+        if let Some(c) = &self.reset_countdown {
+            if c.is_done(&self.clock) {
+                println!("You lose! Score: {}", self.player.score);
+                println!("{}", self.run_stats);
+                self.session_stats.record(&self.run_stats);
+                if let Some(recorder) = self.recorder.take() {
+                    if let Err(e) = recorder.finish(self.player.score).save(REPLAY_PATH) {
+                        eprintln!("Failed to save replay: {}", e);
+                    }
+                    // Only runs played live go on the table, not replays or headless ones.
+                    if self.mode == Mode::Daily {
+                        self.record_daily_best();
+                    }
+                    self.offer_high_score();
+                }
+                if let Some(playback) = self.playback.take() {
+                    playback.verify(self.player.score);
+                }
+                if !self.headless {
+                    if let Err(e) = self.save_event_log() {
+                        eprintln!("Failed to save event log: {}", e);
+                    }
+                }
+                // The run's stats and score are left alone so the game over screen can show them.
+                self.reset_countdown = None;
+                self.screen = Screen::GameOver;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// If the run that just ended made the high score table, ask the player for their initials.
+    fn offer_high_score(&mut self) {
+        if !self.high_scores.qualifies(self.player.score) {
+            return;
+        }
+        self.initials_entry = Some(InitialsEntry::new(HighScoreEntry {
+            score: self.player.score,
+            achieved_at: unix_time(),
+            run_duration: self.run_stats.survived(),
+            difficulty: self.difficulty,
+            initials: String::new(),
+        }));
+    }
+
+    /// Note the run's score against its daily seed, saving the table if it's a new best.
+    fn record_daily_best(&mut self) {
+        if !self.high_scores.record_daily(self.seed, self.player.score) {
+            return;
+        }
+        if let Err(e) = self.high_scores.save() {
+            eprintln!("Failed to save high scores: {}", e);
+        }
+    }
+
+    /// Put a finished entry on the high score table and save the table.
+    fn record_high_score(&mut self, entry: HighScoreEntry) {
+        self.high_scores.insert(entry);
+        if let Err(e) = self.high_scores.save() {
+            eprintln!("Failed to save high scores: {}", e);
+        }
+    }
+
+    /// Write the run's event log to `EVENT_LOG_PATH`, one JSON object per line, followed by the
+    /// obstacle events leading up to the end of the run.
+    fn save_event_log(&self) -> Result<()> {
+        let mut bytes = Vec::new();
+        for event in self.event_log.iter() {
+            serde_json::to_writer(&mut bytes, event).map_err(io::Error::from)?;
+            bytes.push(b'\n');
+        }
+        for event in self.obstacle_events.iter() {
+            serde_json::to_writer(&mut bytes, event).map_err(io::Error::from)?;
+            bytes.push(b'\n');
+        }
+        fs::write(EVENT_LOG_PATH, bytes)?;
+        Ok(())
+    }
+
+    fn update_despawn_obstacles(&mut self) -> Result<()> {
+        // Destroy an obstacle as soon as it's offscreen, leaving its exit sweep to play out on
+        // its own. The points are handed out by `process_events`.
+        let show_exit_sweeps = self.show_exit_sweeps;
+        let exit_sweeps = &mut self.exit_sweeps;
+        let game_events = &mut self.game_events;
+        let events = &mut self.obstacle_events;
+        let tick = self.tick_count;
+        self.obstacles.retain(|&ob| {
+            let res = ob.phase() != ObstaclePhase::Exiting;
+            if !res {
+                events.push(ObstacleEvent::Despawned { id: ob.id, tick });
+                game_events.push(GameEventKind::ObstacleDespawned {
+                    kind: ob.kind,
+                    bonus: ob.kind.despawn_points(),
+                });
+                if show_exit_sweeps {
+                    exit_sweeps.push(ExitSweep::new(&ob));
+                }
+            }
+            res
+        });
+
+        Ok(())
+    }
+}
+
+impl GameState {
+    /// A game on the title screen with the given config. `font` is only used for drawing.
+    fn with_config(config: Config, font: Asset<Font>) -> GameState {
+        let scheme = ColorScheme::from_name(&config.scheme).unwrap_or_else(|| {
+            eprintln!("Unknown color scheme \"{}\", using classic", config.scheme);
+            ColorScheme::classic()
+        });
+        let difficulty = DifficultyPreset::from_name(&config.difficulty).unwrap_or_else(|| {
+            eprintln!("Unknown difficulty \"{}\", using normal", config.difficulty);
+            DifficultyPreset::Normal
+        });
+
+        let skin = SKINS.get(config.skin).cloned().unwrap_or_else(|| {
+            eprintln!("Unknown skin {}, using the color scheme's", config.skin);
+            None
+        });
+        let field = FieldGeometry::default();
+
+        GameState {
+            screen: Screen::Title,
+            error: None,
+            difficulty,
+            mode: Mode::Classic,
+
+            field,
+            obstacles: Vec::new(),
+            obstacle_grid: SpatialGrid::new(COLLISION_GRID_CELL_SIZE),
+            ripples: Vec::new(),
+            exit_sweeps: Vec::new(),
+            show_exit_sweeps: config.exit_sweeps,
+            wrap_edges: config.wrap_edges,
+            mouse_control: config.mouse_control,
+            touch_target: None,
+            player: Player::new(&difficulty.settings(), skin),
+            is_slowmo: false,
+            is_camping: false,
+            rng: StdRng::seed_from_u64(0),
+            seed: 0,
+            seed_override: None,
+
+            recorder: None,
+            playback: None,
+            headless: false,
+            tutorial: None,
+
+            is_running: true,
+            quit_prompt: false,
+            reset_countdown: None,
+
+            paused: false,
+            resume_countdown: None,
+
+            fps_graph: FpsGraph::new(),
+            quality: Quality::Full,
+            frame_spikes: EventLog::new(FRAME_SPIKE_LOG_SIZE),
+            diagnostics: DiagnosticsBuffer::new(DIAGNOSTICS_BUFFER_SIZE),
+            last_frame_spike: None,
+            fps_update_tick: None,
+            fps_readout: None,
+
+            debug: false,
+            danger_map: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_requested: false,
+            #[cfg(feature = "debug")]
+            heatmap: None,
+            peak_obstacles: RollingMax::new(),
+
+            run_stats: RunStats::new(),
+            session_stats: SessionStats::new(),
+            high_scores: HighScoreTable::load_or_default(),
+            initials_entry: None,
+            show_high_scores: false,
+
+            clock: GameClock::new(),
+            ui_clock: GameClock::new(),
+
+            last_spawned: None,
+            spawn_interval: Duration::from_millis(INITIAL_SPAWN_INTERVAL_MS),
+
+            tick_count: 0,
+
+            next_obstacle_id: 0,
+            obstacle_events: EventLog::new(OBSTACLE_EVENT_LOG_SIZE),
+            game_events: Vec::new(),
+            event_log: EventLog::new(GAME_EVENT_LOG_SIZE),
+
+            bomb_flash: None,
+            screen_flash: None,
+            milestones: 0,
+            survival_milestones: 0,
+            last_boss_score: 0,
+            last_magnet_score: 0,
+            popups: Vec::new(),
+            displayed_score: 0.,
+
+            scheme,
+            show_backdrop: config.field_backdrop,
+            field_grid: field.grid_lines(FIELD_GRID_SPACING, FIELD_GRID_LINE_WIDTH),
+            font,
+            font_style: FontStyle::new(FONT_SIZE_PT, scheme.hud),
+        }
+    }
+
+    /// A game for simulating runs without a window, already in a run seeded with `seed`. It uses
+    /// the default config so the run doesn't depend on the machine, and never loads the font.
+    fn new_headless(seed: u64) -> GameState {
+        let font = Asset::new(combinators::err(quicksilver::Error::ContextError(
+            "headless games have no font".to_string(),
+        )));
+        let mut state = GameState::with_config(Config::default(), font);
+        state.headless = true;
+        state.start_seeded_run(seed);
+        state
+    }
+}
+
+impl State for GameState {
+    fn new() -> quicksilver::Result<GameState> {
+        let font = Asset::new(Font::load(FONT_NAME));
+        let mut state = GameState::with_config(Config::load_or_default(), font);
+
+        if let Ok(Some(snapshot)) =
+            saving::load::<Option<GameSnapshot>>(APP_NAME, SNAPSHOT_PROFILE)
+        {
+            // Whether a snapshot is still fresh depends on the wall clock, so deterministic builds
+            // always start fresh.
+            if snapshot.is_recent() && !cfg!(feature = "deterministic") {
+                state.restore(snapshot);
+                state.paused = true;
+                state.start_resume_countdown();
+            }
+        }
+
+        let args: Vec<String> = std::env::args().collect();
+        if let Some(i) = args.iter().position(|arg| arg == SEED_FLAG) {
+            match args.get(i + 1).and_then(|n| n.parse().ok()) {
+                Some(seed) => {
+                    state.seed_override = Some(seed);
+                    state.mode = Mode::Daily;
+                }
+                None => eprintln!("Ignoring {}: it needs a number after it", SEED_FLAG),
+            }
+        }
+
+        if args.iter().any(|arg| arg == REPLAY_FLAG) {
+            match Replay::load(REPLAY_PATH) {
+                Ok(replay) => state.start_replay(replay),
+                Err(e) => eprintln!("Could not load {}: {}", REPLAY_PATH, e),
+            }
+        }
+
+        // Show first-time players the controls before their first real run.
+        if state.screen == Screen::Title && !Tutorial::is_done() {
+            state.start_tutorial();
+        }
+
+        Ok(state)
+    }
+
+    fn event(&mut self, event: &Event, _window: &mut Window) -> quicksilver::Result<()> {
+        match event {
+            Event::Closed => self.shut_down(),
+            Event::Unfocused => {
+                self.paused = true;
+                self.resume_countdown = None;
+            }
+            Event::Focused if self.paused => self.start_resume_countdown(),
+            Event::GamepadButton(_, GamepadButton::FaceUp, ButtonState::Pressed)
+                if self.screen == Screen::Playing =>
+            {
+                if !self.paused {
+                    self.paused = true;
+                } else if self.resume_countdown.is_none() {
+                    self.start_resume_countdown();
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, window: &mut Window) -> quicksilver::Result<()> {
+        fn update_inner(state: &mut GameState, window: &mut Window) -> Result<()> {
+            if !state.is_running {
+                state.shut_down();
+                window.close();
+            }
+
+            state.tick_count += 1;
+            let dt = tick_duration(window);
+            state.ui_clock.advance(dt);
+            state.update_fps_graph(window)?;
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                if window.keyboard()[Key::F12] == ButtonState::Pressed {
+                    state.screenshot_requested = true;
+                }
+            }
+
+            // Alt+Enter is used up by the toggle, so nothing else this tick sees the Enter.
+            if toggle_fullscreen_pressed(window.keyboard()) {
+                let fullscreen = !window.get_fullscreen();
+                window.set_fullscreen(fullscreen);
+                return Ok(());
+            }
+
+            match state.screen {
+                Screen::Title => return state.update_title(window.keyboard()),
+                Screen::GameOver => return state.update_game_over(window.keyboard()),
+                Screen::Error => return state.update_error(window.keyboard()),
+                Screen::Playing => {}
+            }
+
+            if state.quit_prompt {
+                return state.update_quit_prompt(window.keyboard());
+            }
+
+            if state.paused {
+                match &state.resume_countdown {
+                    Some(c) if c.is_done(&state.ui_clock) => {
+                        state.resume_countdown = None;
+                        state.paused = false;
+                    }
+                    _ => return Ok(()),
+                }
+            }
+
+            state.update_handle_keys(window.keyboard())?;
+            let mut input =
+                InputSnapshot::from_keyboard(window.keyboard(), window.gamepads().first());
+            if state.mouse_control {
+                let mouse = window.mouse();
+                state.touch_target = touch_target(&mouse, &state.field);
+                if let Some(target) = state.touch_target {
+                    let slowmo = touch_slowmo(&mouse);
+                    let player = state.player.rect.center();
+                    input = input | InputSnapshot::from_touch(target, player, slowmo);
+                }
+            }
+            state.step(dt, input)
+        }
+
+        let stopwatch = Stopwatch::start();
+        let result = update_inner(self, window);
+        self.diagnostics.push(FrameDiagnostics {
+            frame_id: self.tick_count,
+            update_duration_us: stopwatch.elapsed_us(),
+            draw_duration_us: 0,
+            obstacle_count: self.obstacles.len(),
+            active_particles: self.ripples.len(),
+        });
+        self.handle_error(result)
+    }
+
+    fn draw(&mut self, window: &mut Window) -> quicksilver::Result<()> {
+        fn draw_inner(state: &mut GameState, window: &mut Window) -> Result<()> {
+            window.clear(state.scheme.background)?;
+
+            match state.screen {
+                Screen::Title => state.draw_title(window)?,
+                Screen::Playing => {
+                    let mut commands = state.draw_field_border();
+                    commands.extend(state.draw_danger_map());
+                    commands.extend(state.draw_player());
+                    commands.extend(state.draw_obstacles());
+                    flush_draw_commands(window, commands);
+                    // Text is drawn straight to the window, on top of everything above.
+                    state.draw_popups(window)?;
+                    state.draw_hud(window)?;
+                    state.draw_tutorial(window)?;
+                    state.draw_debug(window)?;
+                    #[cfg(feature = "debug")]
+                    state.draw_debug_overlay(window)?;
+                    state.draw_reset_countdown(window)?;
+                    state.draw_pause(window)?;
+                    state.draw_quit_prompt(window)?;
+                    state.draw_screen_flash(window)?;
+                }
+                Screen::GameOver => {
+                    state.draw_game_over(window)?;
+                    state.draw_hud(window)?;
+                }
+                Screen::Error => state.draw_error(window)?,
+            }
+
+            // Taken once the whole frame is drawn. A failed screenshot isn't worth interrupting
+            // the game for.
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                if state.screenshot_requested {
+                    state.screenshot_requested = false;
+                    match screenshot::capture(window) {
+                        Ok(path) => println!("Saving screenshot to {}", path.display()),
+                        Err(e) => eprintln!("{}", e),
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
+        let stopwatch = Stopwatch::start();
+        let result = draw_inner(self, window);
+        self.diagnostics.log_draw(stopwatch.elapsed_us());
+        self.handle_error(result)
+    }
+}
+
+/// Converts world-centric positioning to playfield-centric positioning.
+trait ToPlayfieldCoordinates {
+    fn on_playfield(&self) -> Rectangle;
+}
 
 impl ToPlayfieldCoordinates for Rectangle {
     fn on_playfield(&self) -> Rectangle {
-        // This assumes the field is going in the center of the screen.
-        self.translate((
-            (WIN_WIDTH as f32 - FIELD_EDGE_LENGTH) / 2.,
-            (WIN_HEIGHT as f32 - FIELD_EDGE_LENGTH) / 2.,
-        ))
+        Display::current().map_playfield(self)
     }
 }
 
 fn main() {
-    run::<GameState>(
-        "First Game",
-        Vector::new(WIN_WIDTH, WIN_HEIGHT),
-        Settings::default(),
-    );
+    let args: Vec<String> = std::env::args().collect();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let flag = args
+            .iter()
+            .position(|arg| arg == BENCH_FLAG || arg == HEADLESS_FLAG);
+        if let Some(i) = flag {
+            let ticks = match args.get(i + 1).and_then(|n| n.parse().ok()) {
+                Some(ticks) => ticks,
+                None => {
+                    eprintln!("Usage: {} <ticks>", args[i]);
+                    std::process::exit(2);
+                }
+            };
+            let result = if args[i] == BENCH_FLAG {
+                bench::run(ticks)
+            } else {
+                bench::run_headless(ticks)
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
+
+    match Display::from_args(&args) {
+        Ok(display) => display.install(),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    }
+
+    let settings = Settings {
+        fullscreen: args.iter().any(|arg| arg == FULLSCREEN_FLAG),
+        ..Settings::default()
+    };
+    run::<GameState>(GAME_TITLE, Display::current().size(), settings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Allowed float error when comparing positions.
+    const EPSILON: f32 = 1e-3;
+
+    /// Play `ticks` ticks of `state` at the deterministic tick rate with no input.
+    fn run_ticks(state: &mut GameState, ticks: u64) {
+        let dt = Duration::from_micros(1_000_000 / DETERMINISTIC_TICKS_PER_SECOND);
+        for _ in 0..ticks {
+            state.tick_count += 1;
+            state.step(dt, InputSnapshot::default()).unwrap();
+        }
+    }
+
+    /// A normal obstacle coming in at `rixel`, just entering the field.
+    fn obstacle_at(rixel: f32) -> Obstacle {
+        let rixel = RixelCoord(rixel);
+        Obstacle {
+            id: 0,
+            rixel,
+            side: Obstacle::rixel_to_direction(rixel).unwrap(),
+            speed: 3.,
+            acceleration: 0.,
+            width: 10.,
+            length: OBSTACLE_BASE_LENGTH,
+            lifetime: 0.,
+            kind: ObstacleKind::Normal,
+            touched_player: false,
+            grazed: false,
+        }
+    }
+
+    /// Whether `rect` lies inside `bounds`, give or take `EPSILON`.
+    fn inside(rect: &Rectangle, bounds: &Rectangle) -> bool {
+        rect.x() >= bounds.x() - EPSILON
+            && rect.y() >= bounds.y() - EPSILON
+            && rect.x() + rect.width() <= bounds.x() + bounds.width() + EPSILON
+            && rect.y() + rect.height() <= bounds.y() + bounds.height() + EPSILON
+    }
+
+    #[test]
+    fn snapshot_restores_to_the_same_state() {
+        let mut state = GameState::new_headless(7);
+        state.mode = Mode::Practice;
+        run_ticks(&mut state, 300);
+        state.player.add_score(2500);
+        let snapshot = state.snapshot().unwrap();
+        let saved = serde_json::to_string(&snapshot).unwrap();
+
+        let mut restored = GameState::new_headless(0);
+        restored.restore(serde_json::from_str(&saved).unwrap());
+        let resaved = restored.snapshot().unwrap();
+
+        // Converting the time since the last spawn to game time and back can be off by a
+        // microsecond.
+        let since = |s: &GameSnapshot| s.since_last_spawn.unwrap().as_secs_f64();
+        assert!((since(&snapshot) - since(&resaved)).abs() < 1e-3);
+        let fields = |s: &GameSnapshot| {
+            let mut value = serde_json::to_value(s).unwrap();
+            let map = value.as_object_mut().unwrap();
+            map.remove("saved_at");
+            map.remove("since_last_spawn");
+            value
+        };
+        assert_eq!(fields(&snapshot), fields(&resaved));
+        assert!(!snapshot.obstacles.is_empty());
+        assert_eq!(restored.player.score, 2500);
+        assert_eq!(restored.displayed_score, 2500.);
+    }
+
+    #[test]
+    fn spawned_obstacles_never_straddle_a_corner() {
+        let field = FieldGeometry::default();
+        let settings = DifficultySettings::normal();
+        let mut rng = StdRng::seed_from_u64(53);
+        for id in 0..10_000 {
+            let side = Direction::random(&mut rng, &[1.; 4]);
+            let score = rng.gen_range(0, 20_000);
+            let mut ob = Obstacle::spawn(id, side, &mut rng, score, &settings);
+            assert_eq!(Obstacle::rixel_to_direction(ob.rixel).unwrap(), side);
+            // Anywhere in its crossing, the whole obstacle is on the field.
+            ob.lifetime = rng.gen_range(0., ob.total_lifetime());
+            assert!(inside(&ob.rectangle(), &field.rect()), "{:?}", ob);
+        }
+    }
+
+    #[test]
+    fn obstacle_kinds_follow_their_weights() {
+        let mut rng = StdRng::seed_from_u64(54);
+        let samples = 100_000;
+        for &score in &[0, 5_000, 20_000] {
+            let weights: Vec<f32> = (0..ObstacleKind::ALL.len())
+                .map(|i| {
+                    OBSTACLE_KIND_WEIGHTS_BASE[i]
+                        + OBSTACLE_KIND_WEIGHTS_PER_1000_SCORE[i] * score as f32 / 1000.
+                })
+                .collect();
+            let total: f32 = weights.iter().sum();
+            let mut counts = [0; 8];
+            for _ in 0..samples {
+                let kind = ObstacleKind::random(&mut rng, score);
+                counts[ObstacleKind::ALL.iter().position(|&k| k == kind).unwrap()] += 1;
+            }
+            for (i, &count) in counts.iter().enumerate() {
+                let share = count as f32 / samples as f32;
+                assert!(
+                    (share - weights[i] / total).abs() < 0.01,
+                    "{:?} at score {} came up {} of the time",
+                    ObstacleKind::ALL[i],
+                    score,
+                    share
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn opposite_is_straight_across_the_field() {
+        let cases = [
+            // North at x = 100 leaves through South at x = 100.
+            (100., 1400.),
+            // East at y = 100 leaves through West at y = 100.
+            (600., 1900.),
+            // South at x = 300 leaves through North at x = 300.
+            (1200., 300.),
+            // West at y = 300 leaves through East at y = 300.
+            (1700., 800.),
+            // The start of East is across from the end of West, which is back at 0.
+            (500., 0.),
+        ];
+        for &(rixel, exit) in &cases {
+            let ob = obstacle_at(rixel);
+            assert_eq!(ob.opposite(), RixelCoord(exit), "opposite of {}", rixel);
+
+            let PixelCoord(entry) = ob.rixel.into();
+            let PixelCoord(exit) = ob.opposite().into();
+            let across = entry - ob.side.unit_vector() * ob.crossing_distance();
+            assert!((exit - across).len() < EPSILON, "opposite of {}", rixel);
+        }
+    }
+
+    #[test]
+    fn sides_split_a_rectangular_field_at_its_corners() {
+        let field = FieldGeometry::new(600., 400.);
+        assert_eq!(field.perimeter(), 2000.);
+        let starts: Vec<f32> = Direction::ALL
+            .iter()
+            .map(|side| side.side_start_on(&field))
+            .collect();
+        assert_eq!(starts, [0., 600., 1000., 1600.]);
+
+        let side_at = |rixel| Direction::of_rixel(RixelCoord(rixel), &field);
+        assert_eq!(side_at(-0.1), None);
+        assert_eq!(side_at(0.), Some(Direction::North));
+        assert_eq!(side_at(599.9), Some(Direction::North));
+        assert_eq!(side_at(600.), Some(Direction::East));
+        assert_eq!(side_at(999.9), Some(Direction::East));
+        assert_eq!(side_at(1000.), Some(Direction::South));
+        assert_eq!(side_at(1599.9), Some(Direction::South));
+        assert_eq!(side_at(1600.), Some(Direction::West));
+        assert_eq!(side_at(1999.9), Some(Direction::West));
+        assert_eq!(side_at(2000.), None);
+
+        // Each side runs from its own corner to the next side's.
+        for (i, side) in Direction::ALL.iter().enumerate() {
+            let next = Direction::ALL[(i + 1) % 4];
+            let end = side.start_corner_on(&field)
+                + side.clockwise_vector() * side.side_length_on(&field);
+            assert_eq!(end, next.start_corner_on(&field));
+        }
+        assert_eq!(Direction::North.crossing_distance_on(&field), 400.);
+        assert_eq!(Direction::East.crossing_distance_on(&field), 600.);
+    }
+
+    /// A player at rest in the middle of the default field.
+    fn player_in_middle() -> Player {
+        let mut player = Player::new(&DifficultySettings::normal(), None);
+        player.rect.pos = Vector::new(200., 200.);
+        player
+    }
+
+    #[test]
+    fn movement_speed_is_capped() {
+        let field = FieldGeometry::default();
+        for &(slowmo, cap) in &[
+            (false, PLAYER_SPEED),
+            (true, PLAYER_SPEED / PLAYER_SLOWMO_FACTOR),
+        ] {
+            let mut player = player_in_middle();
+            let input = MovementInput {
+                direction: Vector::new(0, 1),
+                slowmo,
+            };
+            for _ in 0..20 {
+                apply_movement(&input, &mut player, &field, 1., false);
+                assert!(player.velocity.len() <= cap + EPSILON);
+            }
+            // Holding a direction is enough to reach the cap.
+            assert!((player.velocity.len() - cap).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn friction_slows_the_player_to_a_stop() {
+        let field = FieldGeometry::default();
+        let mut player = player_in_middle();
+        player.velocity = Vector::new(4., 0.);
+        let input = MovementInput {
+            direction: Vector::ZERO,
+            slowmo: false,
+        };
+
+        apply_movement(&input, &mut player, &field, 1., false);
+        assert!((player.velocity.x - 4. * PLAYER_FRICTION).abs() < EPSILON);
+        assert!((player.rect.x() - (200. + 4. * PLAYER_FRICTION)).abs() < EPSILON);
+
+        for _ in 0..100 {
+            apply_movement(&input, &mut player, &field, 1., false);
+        }
+        assert!(player.velocity.len() < EPSILON);
+    }
+
+    #[test]
+    fn movement_stops_at_the_walls() {
+        let field = FieldGeometry::default();
+        let mut player = player_in_middle();
+        player.rect.pos = Vector::new(field.width - PLAYER_SIZE - 1., 200.);
+        player.velocity = Vector::new(PLAYER_SPEED, 0.);
+        let input = MovementInput {
+            direction: Vector::new(1, 0),
+            slowmo: false,
+        };
+        let hit = apply_movement(&input, &mut player, &field, 1., false);
+        assert_eq!(hit, BoundaryHit { x: true, y: false });
+        assert_eq!(player.rect.x(), field.width - PLAYER_SIZE);
+    }
+
+    #[test]
+    fn obstacles_keep_moving_during_the_reset_countdown() {
+        let mut state = GameState::new_headless(63);
+        state.spawn_obstacle_at(RixelCoord(400.), 3., 10., OBSTACLE_BASE_LENGTH).unwrap();
+        state.obstacles[0].lifetime = 50.;
+        state.reset_countdown = Some(Countdown::new(&state.clock, Duration::from_secs(2)));
+
+        run_ticks(&mut state, 10);
+        assert!(state.reset_countdown.is_some());
+        assert_eq!(state.obstacles[0].lifetime, 60.);
+    }
+
+    #[test]
+    fn sides_follow_their_weights() {
+        let mut rng = StdRng::seed_from_u64(65);
+        let samples = 100_000;
+        let mut adaptive = DifficultySettings::hard().side_weights;
+        adaptive[Direction::West as usize] *= ADAPTIVE_SIDE_BIAS;
+        for weights in &[[1., 2., 3., 4.], [1., 0., 0., 1.], adaptive] {
+            let total: f32 = weights.iter().sum();
+            let mut counts = [0; 4];
+            for _ in 0..samples {
+                counts[Direction::random(&mut rng, weights) as usize] += 1;
+            }
+            for (i, &count) in counts.iter().enumerate() {
+                let share = count as f32 / samples as f32;
+                assert!(
+                    (share - weights[i] / total).abs() < 0.01,
+                    "{:?} came up {} of the time with weights {:?}",
+                    Direction::ALL[i],
+                    share,
+                    weights
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn obstacle_phases_split_at_zero_and_total_lifetime() {
+        let mut ob = obstacle_at(100.);
+        let total = ob.total_lifetime();
+        let cases = [
+            (-1., ObstaclePhase::Warning, 0.),
+            (0., ObstaclePhase::Active, 0.),
+            (total / 2., ObstaclePhase::Active, 0.5),
+            (total, ObstaclePhase::Active, 1.),
+            (total + 1., ObstaclePhase::Exiting, 1.),
+        ];
+        for &(lifetime, phase, age) in &cases {
+            ob.lifetime = lifetime;
+            assert_eq!(ob.phase(), phase, "at lifetime {}", lifetime);
+            assert!((ob.age_fraction() - age).abs() < EPSILON, "at lifetime {}", lifetime);
+        }
+    }
+
+    #[test]
+    fn telegraphed_obstacles_are_not_grazed() {
+        let mut state = GameState::new_headless(66);
+        // Right over the collector of a player sitting in the top-left corner.
+        state.spawn_obstacle_at(RixelCoord(20.), 3., 10., OBSTACLE_BASE_LENGTH).unwrap();
+        run_ticks(&mut state, OBSTACLE_PRE_SPAWN_WARN_TIME as u64);
+
+        assert_eq!(state.obstacles[0].lifetime, 0.);
+        assert_eq!(state.player.score, 0);
+        assert!(state.obstacles.iter().all(|ob| !ob.grazed));
+        assert!(state.reset_countdown.is_none());
+    }
+
+    #[test]
+    fn spawning_stops_at_the_obstacle_cap() {
+        for &difficulty in &DifficultyPreset::ALL {
+            let mut state = GameState::new_headless(66);
+            state.difficulty = difficulty;
+            state.mode = Mode::Practice;
+            let cap = difficulty.settings().max_obstacles;
+            for _ in 0..600 {
+                // Due a spawn every tick.
+                state.last_spawned = None;
+                run_ticks(&mut state, 1);
+                // Bosses come in at score milestones whatever the cap.
+                let spawned = state
+                    .obstacles
+                    .iter()
+                    .filter(|ob| ob.kind != ObstacleKind::Boss)
+                    .count();
+                assert!(spawned <= cap, "{} obstacles on {}", spawned, difficulty.name());
+            }
+            assert!(state.obstacles.len() >= cap);
+        }
+    }
+
+    #[test]
+    fn obstacles_spawn_once_the_interval_has_passed() {
+        let mut state = GameState::new_headless(75);
+        state.mode = Mode::Practice;
+        state.last_spawned = Some(state.clock.now());
+        let interval = state.effective_spawn_interval();
+
+        state.clock.advance(interval);
+        state.update_spawn_obstacles().unwrap();
+        assert!(state.obstacles.is_empty());
+
+        state.clock.advance(Duration::from_millis(1));
+        state.update_spawn_obstacles().unwrap();
+        assert_eq!(state.obstacles.len(), 1);
+        assert_eq!(state.last_spawned, Some(state.clock.now()));
+    }
+
+    #[test]
+    fn fps_readout_refreshes_on_its_interval() {
+        let mut state = GameState::new_headless(75);
+        state.fps_graph.log_fps(60.);
+        state.update_fps_readout();
+        assert_eq!(state.fps_readout, Some((60., 60.)));
+
+        state.fps_graph.log_fps(30.);
+        state.tick_count += FPS_UPDATE_INTERVAL_TICKS;
+        state.update_fps_readout();
+        assert_eq!(state.fps_readout, Some((60., 60.)));
+
+        state.tick_count += 1;
+        state.update_fps_readout();
+        assert_eq!(state.fps_readout, Some((45., 30.)));
+    }
+
+    #[test]
+    fn a_wave_of_four_comes_from_every_side() {
+        let settings = DifficultySettings::normal();
+        let mut rng = StdRng::seed_from_u64(78);
+        for _ in 0..1_000 {
+            let wave = Obstacle::spawn_wave(0, 4, &mut rng, 0, &settings);
+            assert_eq!(wave.len(), 4);
+            for side in &Direction::ALL {
+                assert!(wave.iter().any(|ob| ob.side == *side), "no obstacle from {:?}", side);
+            }
+        }
+    }
+
+    #[test]
+    fn the_obstacle_grid_finds_what_a_full_scan_does() {
+        let settings = DifficultySettings::normal();
+        let mut rng = StdRng::seed_from_u64(90);
+        for _ in 0..300 {
+            let mut state = GameState::new_headless(0);
+            let count = rng.gen_range(0, 40);
+            state.obstacles = (0..count)
+                .map(|id| {
+                    let side = Direction::ALL[rng.gen_range(0, 4)];
+                    let mut ob = Obstacle::spawn(id, side, &mut rng, 0, &settings);
+                    ob.lifetime = rng.gen_range(-20., ob.total_lifetime() + 20.);
+                    ob
+                })
+                .collect();
+            // Anywhere, including hanging off the far edges where the player wraps.
+            state.player.rect.pos = Vector::new(
+                rng.gen_range(0., state.field.width),
+                rng.gen_range(0., state.field.height),
+            );
+            state.fill_obstacle_grid();
+
+            let collector = state.player.collector_rectangle();
+            let touching: Vec<usize> = (0..state.obstacles.len())
+                .filter(|&i| match state.obstacles[i].hitbox() {
+                    Some(rect) => {
+                        state.player.overlaps(&state.field, &rect)
+                            || collector.overlaps_rectangle(&rect)
+                    }
+                    None => false,
+                })
+                .collect();
+            assert_eq!(state.obstacles_near_player(), touching);
+        }
+    }
+
+    #[test]
+    fn obstacles_only_have_a_hitbox_inside_the_field() {
+        let mut ob = obstacle_at(100.);
+        let total = ob.total_lifetime();
+        for &(lifetime, inside) in &[
+            (-1., false),
+            (0., false),
+            (0.5, true),
+            (total - 0.5, true),
+            (total, false),
+            (total + 1., false),
+        ] {
+            ob.lifetime = lifetime;
+            assert_eq!(ob.hitbox().is_some(), inside, "at lifetime {}", lifetime);
+            // The rectangle itself never has a negative size, whatever the phase.
+            let rect = ob.rectangle();
+            assert!(rect.width() >= 0. && rect.height() >= 0., "at lifetime {}", lifetime);
+        }
+    }
+
+    #[test]
+    fn obstacle_extent_at_phase_boundaries() {
+        let ob = obstacle_at(100.);
+        let (length, crossing, total) = (ob.length, ob.crossing_distance(), ob.total_lifetime());
+        let hide = OBSTACLE_HIDE_DELAY as f32;
+        let cases = [
+            // Telegraphed: nothing in the field yet.
+            (-1., (0., 0.)),
+            // Just entering.
+            (0., (0., 0.)),
+            // All the way in.
+            (length / ob.speed, (length, length)),
+            // Front reaching the far side.
+            (crossing / ob.speed, (crossing, length)),
+            // Back leaving the far side.
+            (total, (crossing, 0.)),
+            // Gone, waiting to be removed.
+            (total + hide, (crossing, 0.)),
+        ];
+        for &(lifetime, (distance, len)) in &cases {
+            let (d, l) = ob.extent_at(lifetime);
+            assert!((d - distance).abs() < EPSILON, "distance {} at lifetime {}", d, lifetime);
+            assert!((l - len).abs() < EPSILON, "length {} at lifetime {}", l, lifetime);
+        }
+
+        // A telegraphed obstacle has no area on the field.
+        let mut warning = ob;
+        warning.lifetime = -(OBSTACLE_PRE_SPAWN_WARN_TIME as f32) / 2.;
+        assert_eq!(warning.phase(), ObstaclePhase::Warning);
+        assert!(warning.hitbox().is_none());
+        let rect = warning.rectangle();
+        assert_eq!(rect.width() * rect.height(), 0.);
+    }
+
+    #[test]
+    fn runs_on_the_same_seed_spawn_the_same_obstacles() {
+        let spawns = |mode: Mode| {
+            let mut state = GameState::new_headless(96);
+            state.mode = mode;
+            state.start_seeded_run(96);
+            run_ticks(&mut state, 1_000);
+            state
+                .obstacle_events
+                .iter()
+                .filter(|event| matches!(event, ObstacleEvent::Spawned { .. }))
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        for &mode in &Mode::ALL {
+            let first = spawns(mode);
+            assert!(!first.is_empty());
+            assert_eq!(first, spawns(mode), "{} runs differ", mode.name());
+        }
+    }
+
+    #[test]
+    fn warning_geometry_at_phase_boundaries() {
+        let mut ob = obstacle_at(100.);
+        let warn = ob.pre_spawn_warn_time();
+        let crossing = ob.crossing_distance();
+        let total = ob.total_lifetime();
+        let length_at = |ob: &mut Obstacle, lifetime: f32| {
+            ob.lifetime = lifetime;
+            ob.warning_geometry().map(|(rixel, distance, length)| {
+                assert_eq!(rixel, ob.rixel);
+                // The line always reaches back to the border.
+                assert_eq!(distance, length);
+                length
+            })
+        };
+
+        // Nothing yet on the tick the obstacle is first telegraphed.
+        assert_eq!(length_at(&mut ob, -warn), None);
+        assert_eq!(length_at(&mut ob, -warn + 1.), Some(OBSTACLE_WARNING_MOVE_SPEED));
+        // The line sweeps across, then holds until the obstacle comes in.
+        let mut last = 0.;
+        for tick in 1..warn as u32 {
+            let length = length_at(&mut ob, -warn + tick as f32).unwrap();
+            assert!(length >= last && length <= crossing, "at tick {}", tick);
+            last = length;
+        }
+        assert_eq!(length_at(&mut ob, -1.), Some(crossing));
+        // No gap on the frame the obstacle enters, nor while it crosses.
+        assert_eq!(length_at(&mut ob, 0.), Some(crossing));
+        for tick in 0..=total as u32 {
+            assert_eq!(length_at(&mut ob, tick as f32), Some(crossing), "at tick {}", tick);
+        }
+        assert_eq!(length_at(&mut ob, total), Some(crossing));
+        // The exit sweep takes over once it has left.
+        assert_eq!(length_at(&mut ob, total + 1.), None);
+        assert_eq!(length_at(&mut ob, total + OBSTACLE_HIDE_DELAY as f32), None);
+    }
+
+    #[test]
+    fn the_highest_score_spawns_at_the_minimum_interval() {
+        let mut state = GameState::new_headless(101);
+        state.spawn_interval = state.difficulty.settings().spawn_interval(u32::MAX);
+        assert!(!state.boss_on_field());
+        assert_eq!(state.spawn_interval_millis(), MIN_SPAWN_INTERVAL_MS);
+    }
+
+    /// Queue `events` on `state` and apply them.
+    fn process(state: &mut GameState, events: &[GameEventKind]) {
+        state.game_events.extend_from_slice(events);
+        state.process_events();
+    }
+
+    #[test]
+    fn grazes_and_dodges_score_points() {
+        let mut state = GameState::new_headless(87);
+        process(
+            &mut state,
+            &[
+                GameEventKind::Grazed {
+                    kind: ObstacleKind::Normal,
+                    first: true,
+                },
+                GameEventKind::Grazed {
+                    kind: ObstacleKind::Gold,
+                    first: false,
+                },
+                GameEventKind::ObstacleDespawned {
+                    kind: ObstacleKind::Normal,
+                    bonus: 10,
+                },
+            ],
+        );
+        assert_eq!(state.player.score, 1 + GOLD_OBSTACLE_GRAZE_POINTS + 10);
+        assert!(state.player.graze_flash.is_some());
+        assert!(state.popups.is_empty());
+        assert!(state.reset_countdown.is_none());
+        assert!(state.game_events.is_empty());
+
+        // Only the first tick over an obstacle flashes.
+        state.player.graze_flash = None;
+        process(
+            &mut state,
+            &[GameEventKind::Grazed {
+                kind: ObstacleKind::Normal,
+                first: false,
+            }],
+        );
+        assert!(state.player.graze_flash.is_none());
+    }
+
+    #[test]
+    fn a_boss_leaving_shows_its_bonus() {
+        let mut state = GameState::new_headless(87);
+        process(
+            &mut state,
+            &[GameEventKind::ObstacleDespawned {
+                kind: ObstacleKind::Boss,
+                bonus: 500,
+            }],
+        );
+        assert_eq!(state.player.score, 500);
+        assert_eq!(state.popups.len(), 1);
+        assert_eq!(state.popups[0].text, "+500");
+    }
+
+    #[test]
+    fn the_last_life_lost_stops_scoring() {
+        let mut state = GameState::new_headless(87);
+        assert_eq!(state.player.lives, 1);
+        process(
+            &mut state,
+            &[
+                GameEventKind::ObstacleDespawned {
+                    kind: ObstacleKind::Normal,
+                    bonus: 10,
+                },
+                GameEventKind::PlayerHit,
+                GameEventKind::ObstacleDespawned {
+                    kind: ObstacleKind::Boss,
+                    bonus: 500,
+                },
+                GameEventKind::Grazed {
+                    kind: ObstacleKind::Normal,
+                    first: true,
+                },
+            ],
+        );
+        assert!(state.reset_countdown.is_some());
+        assert_eq!(state.player.score, 10);
+        assert!(state.player.graze_flash.is_none());
+        assert!(state.popups.is_empty());
+    }
+
+    #[test]
+    fn a_spare_life_clears_what_hit_the_player() {
+        let mut state = GameState::new_headless(87);
+        state.player.lives = 2;
+        // One obstacle right over the player in the top-left corner, one far from it.
+        state.spawn_obstacle_at(RixelCoord(10.), 3., 10., OBSTACLE_BASE_LENGTH).unwrap();
+        state.spawn_obstacle_at(RixelCoord(400.), 3., 10., OBSTACLE_BASE_LENGTH).unwrap();
+        for ob in &mut state.obstacles {
+            ob.lifetime = 10.;
+        }
+        process(&mut state, &[GameEventKind::PlayerHit]);
+        assert_eq!(state.player.lives, 1);
+        assert!(state.reset_countdown.is_none());
+        assert_eq!(state.obstacles.len(), 1);
+        assert_eq!(state.obstacles[0].rixel, RixelCoord(400.));
+    }
+
+    #[test]
+    fn obstacles_about_to_leave_are_drawn_last() {
+        let at = |lifetime| Obstacle {
+            lifetime,
+            ..obstacle_at(100.)
+        };
+        let (warning, fresh, leaving) = (at(-10.), at(5.), at(100.));
+        assert_eq!(warning.draw_order(&fresh), Ordering::Less);
+        assert_eq!(fresh.draw_order(&warning), Ordering::Greater);
+        // Less time left sorts later, so it is drawn on top.
+        assert_eq!(leaving.draw_order(&fresh), Ordering::Greater);
+        assert_eq!(fresh.draw_order(&leaving), Ordering::Less);
+        assert_eq!(fresh.draw_order(&at(5.)), Ordering::Equal);
+        // A warning sorts first even when it has less time left than an active obstacle.
+        let slow = Obstacle {
+            speed: 0.5,
+            ..at(0.)
+        };
+        assert!(warning.remaining_lifetime() < slow.remaining_lifetime());
+        assert_eq!(warning.draw_order(&slow), Ordering::Less);
+
+        let mut obstacles = [leaving, warning, fresh];
+        obstacles.sort_by(|a, b| a.draw_order(b));
+        let lifetimes: Vec<f32> = obstacles.iter().map(|ob| ob.lifetime).collect();
+        assert_eq!(lifetimes, [-10., 5., 100.]);
+    }
+
+    #[test]
+    fn rixels_and_pixels_round_trip_at_the_corners() {
+        let corners = [
+            (0., (0., 0.)),
+            (FIELD_WIDTH, (FIELD_WIDTH, 0.)),
+            (FIELD_WIDTH + FIELD_HEIGHT, (FIELD_WIDTH, FIELD_HEIGHT)),
+            (FIELD_WIDTH * 2. + FIELD_HEIGHT, (0., FIELD_HEIGHT)),
+        ];
+        for &(rixel, pos) in &corners {
+            let pixel = PixelCoord::from(RixelCoord(rixel));
+            assert_eq!(pixel, PixelCoord(Vector::new(pos.0, pos.1)));
+            assert_eq!(RixelCoord::try_from(pixel).unwrap(), RixelCoord(rixel));
+        }
+        // The perimeter wraps back to where North starts.
+        let pixel = PixelCoord::from(RixelCoord(FIELD_PERIMETER));
+        assert_eq!(pixel, PixelCoord(Vector::new(0., 0.)));
+        assert_eq!(RixelCoord::try_from(pixel).unwrap(), RixelCoord(0.));
+
+        let middle = Vector::new(FIELD_WIDTH / 2., FIELD_HEIGHT / 2.);
+        let off = RixelCoord::try_from(PixelCoord(middle));
+        assert!(matches!(off, Err(Error::PixelOffBorder(pos)) if pos == middle));
+    }
 }
@@ -0,0 +1,200 @@
+use std::{fs, io::Read};
+
+use super::consts::difficulty::DifficultyPreset;
+use super::error::{Error, Result};
+use super::input::InputSnapshot;
+use super::Mode;
+
+const MAGIC: &[u8; 4] = b"QSRP";
+const VERSION: u8 = 1;
+
+/// Each tick's input is stored as one byte, a bit per field.
+impl InputSnapshot {
+    fn to_byte(self) -> u8 {
+        [
+            self.left,
+            self.down,
+            self.up,
+            self.right,
+            self.slowmo,
+            self.bomb,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |byte, (i, &set)| byte | ((set as u8) << i))
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        let bit = |i: u8| byte & (1 << i) != 0;
+        InputSnapshot {
+            left: bit(0),
+            down: bit(1),
+            up: bit(2),
+            right: bit(3),
+            slowmo: bit(4),
+            bomb: bit(5),
+        }
+    }
+}
+
+/// Records the input of a run as it is played, along with everything else needed to play it back.
+pub struct ReplayRecorder {
+    seed: u64,
+    difficulty: DifficultyPreset,
+    mode: Mode,
+    /// Input for each simulated tick of the run, paired with the tick's index.
+    events: Vec<(u64, InputSnapshot)>,
+}
+
+impl ReplayRecorder {
+    pub fn new(seed: u64, difficulty: DifficultyPreset, mode: Mode) -> Self {
+        ReplayRecorder {
+            seed,
+            difficulty,
+            mode,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, input: InputSnapshot) {
+        let tick = self.events.len() as u64;
+        self.events.push((tick, input));
+    }
+
+    /// Finish the recording, noting the score the run ended with.
+    pub fn finish(self, final_score: u32) -> Replay {
+        Replay {
+            seed: self.seed,
+            difficulty: self.difficulty,
+            mode: self.mode,
+            final_score,
+            events: self.events,
+        }
+    }
+}
+
+/// A finished recording of a run.
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: DifficultyPreset,
+    pub mode: Mode,
+    pub final_score: u32,
+    events: Vec<(u64, InputSnapshot)>,
+}
+
+impl Replay {
+    /// Write the replay to `path`. All numbers are little-endian.
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut bytes = Vec::with_capacity(26 + self.events.len() * 9);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(index_of(&DifficultyPreset::ALL, self.difficulty));
+        bytes.push(index_of(&Mode::ALL, self.mode));
+        bytes.extend_from_slice(&self.final_score.to_le_bytes());
+        bytes.extend_from_slice(&(self.events.len() as u64).to_le_bytes());
+        for (tick, input) in &self.events {
+            bytes.extend_from_slice(&tick.to_le_bytes());
+            bytes.push(input.to_byte());
+        }
+
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Replay> {
+        let bytes = fs::read(path)?;
+        let mut reader = &bytes[..];
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::InvalidReplay("not a replay file"));
+        }
+        if read_u8(&mut reader)? != VERSION {
+            return Err(Error::InvalidReplay("unsupported replay version"));
+        }
+
+        let seed = read_u64(&mut reader)?;
+        let difficulty = *DifficultyPreset::ALL
+            .get(read_u8(&mut reader)? as usize)
+            .ok_or(Error::InvalidReplay("unknown difficulty"))?;
+        let mode = *Mode::ALL
+            .get(read_u8(&mut reader)? as usize)
+            .ok_or(Error::InvalidReplay("unknown mode"))?;
+        let final_score = read_u32(&mut reader)?;
+
+        let count = read_u64(&mut reader)?;
+        let mut events = Vec::new();
+        for _ in 0..count {
+            let tick = read_u64(&mut reader)?;
+            events.push((tick, InputSnapshot::from_byte(read_u8(&mut reader)?)));
+        }
+
+        Ok(Replay {
+            seed,
+            difficulty,
+            mode,
+            final_score,
+            events,
+        })
+    }
+}
+
+/// Feeds a replay's recorded input back one tick at a time.
+pub struct ReplayPlayback {
+    replay: Replay,
+    next: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayback { replay, next: 0 }
+    }
+
+    /// Input for the next tick. Once the recording runs out, the player stops doing anything.
+    pub fn next_input(&mut self) -> InputSnapshot {
+        let input = self
+            .replay
+            .events
+            .get(self.next)
+            .map(|&(_, input)| input)
+            .unwrap_or_default();
+        self.next += 1;
+        input
+    }
+
+    /// Report whether the run ended the same way it did when it was recorded.
+    pub fn verify(&self, final_score: u32) {
+        if final_score == self.replay.final_score {
+            println!("Replay verified: score {}", final_score);
+        } else {
+            println!(
+                "Replay diverged: recorded score {}, got {}",
+                self.replay.final_score, final_score
+            );
+        }
+    }
+}
+
+fn index_of<T: PartialEq>(all: &[T], item: T) -> u8 {
+    all.iter().position(|x| *x == item).unwrap_or(0) as u8
+}
+
+fn read_u8(reader: &mut &[u8]) -> Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut &[u8]) -> Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
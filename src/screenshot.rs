@@ -0,0 +1,37 @@
+use quicksilver::{graphics::PixelFormat, lifecycle::Window};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::consts::system::SCREENSHOT_DIR;
+use super::error::{Error, Result};
+
+/// Grab everything drawn to `window` so far this frame and save it as a PNG in `SCREENSHOT_DIR`,
+/// named after the time it was taken. Encoding happens on another thread so the game doesn't
+/// stall; if it fails, the error is logged from there. Returns where the PNG is being written.
+pub fn capture(window: &mut Window) -> Result<PathBuf> {
+    window.flush()?;
+    let image = window.screenshot(PixelFormat::RGBA);
+
+    fs::create_dir_all(SCREENSHOT_DIR)
+        .map_err(|e| Error::Screenshot(format!("could not create {}: {}", SCREENSHOT_DIR, e)))?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let path =
+        Path::new(SCREENSHOT_DIR).join(format!("{}-{:03}.png", now.as_secs(), now.subsec_millis()));
+
+    let out = path.clone();
+    thread::spawn(move || {
+        if let Err(e) = image.save(&out) {
+            let e = Error::Screenshot(format!("could not write {}: {}", out.display(), e));
+            eprintln!("{}", e);
+        }
+    });
+
+    Ok(path)
+}
@@ -0,0 +1,137 @@
+use std::{fmt, time::Duration};
+
+use super::consts::system::SECS_PER_DAY;
+
+/// Statistics for a single run, from spawn until the player is hit.
+pub struct RunStats {
+    survived: Duration,
+    pub dodged: u32,
+    graze_streak: u32,
+    pub best_graze_streak: u32,
+    obstacle_ticks: u64,
+    ticks: u64,
+}
+
+impl RunStats {
+    pub fn new() -> Self {
+        RunStats {
+            survived: Duration::from_secs(0),
+            dodged: 0,
+            graze_streak: 0,
+            best_graze_streak: 0,
+            obstacle_ticks: 0,
+            ticks: 0,
+        }
+    }
+
+    /// How long the player has survived so far.
+    pub fn survived(&self) -> Duration {
+        self.survived
+    }
+
+    /// Record one update tick of `dt` spent alive. `grazed` is whether the collector touched any
+    /// obstacle this tick; consecutive grazing ticks make up a streak.
+    pub fn log_tick(&mut self, dt: Duration, obstacle_count: usize, grazed: bool) {
+        self.survived += dt;
+        self.ticks += 1;
+        self.obstacle_ticks += obstacle_count as u64;
+
+        if grazed {
+            self.graze_streak += 1;
+            self.best_graze_streak = self.best_graze_streak.max(self.graze_streak);
+        } else {
+            self.graze_streak = 0;
+        }
+    }
+
+    pub fn log_dodge(&mut self) {
+        self.dodged += 1;
+    }
+
+    pub fn average_obstacles(&self) -> f32 {
+        if self.ticks == 0 {
+            0.
+        } else {
+            self.obstacle_ticks as f32 / self.ticks as f32
+        }
+    }
+
+    /// One line per stat, for the game over screen.
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Survived: {}", format_duration(self.survived())),
+            format!("Obstacles dodged: {}", self.dodged),
+            format!("Best graze streak: {}", self.best_graze_streak),
+            format!("Avg. on screen: {:.2}", self.average_obstacles()),
+        ]
+    }
+}
+
+impl fmt::Display for RunStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "  {}", self.lines().join("\n  "))
+    }
+}
+
+/// Bests accumulated across every run in this process.
+#[derive(Default)]
+pub struct SessionStats {
+    pub runs: u32,
+    pub best_survived: Duration,
+    pub most_dodged: u32,
+    pub best_graze_streak: u32,
+    pub best_average_obstacles: f32,
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn record(&mut self, run: &RunStats) {
+        self.runs += 1;
+        self.best_survived = self.best_survived.max(run.survived());
+        self.most_dodged = self.most_dodged.max(run.dodged);
+        self.best_graze_streak = self.best_graze_streak.max(run.best_graze_streak);
+        self.best_average_obstacles = self.best_average_obstacles.max(run.average_obstacles());
+    }
+
+    /// One line per stat, for drawing in the HUD.
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Best time {}", format_duration(self.best_survived)),
+            format!("Most dodged {}", self.most_dodged),
+            format!("Best streak {}", self.best_graze_streak),
+        ]
+    }
+}
+
+pub fn format_duration(d: Duration) -> String {
+    format!("{}.{:01}s", d.as_secs(), d.subsec_millis() / 100)
+}
+
+/// Format `d` like a stopwatch, e.g. `1:05.3`.
+pub fn format_clock(d: Duration) -> String {
+    let (secs, tenths) = (d.as_secs(), d.subsec_millis() / 100);
+    format!("{}:{:02}.{:01}", secs / 60, secs % 60, tenths)
+}
+
+/// Format `unix_secs`, seconds since the Unix epoch, as a UTC date like `2019-07-04`.
+pub fn format_date(unix_secs: u64) -> String {
+    // Howard Hinnant's days-to-civil algorithm, with eras of 400 years starting on March 1st.
+    let days = unix_secs / SECS_PER_DAY + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+    format!("{}-{:02}-{:02}", year, month, day)
+}
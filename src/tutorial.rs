@@ -0,0 +1,212 @@
+use quicksilver::{
+    geom::{Rectangle, Shape, Vector},
+    input::{ButtonState, Key, Keyboard},
+    lifecycle::Window,
+    saving,
+};
+use std::{convert::TryFrom, time::Duration};
+
+use super::consts::game::*;
+use super::consts::graphics::{FIELD_EDGE_BORDER_WIDTH, FONT_SIZE_PT};
+use super::consts::system::{APP_NAME, TUTORIAL_PROFILE};
+use super::error::Result;
+use super::graphics::draw_text_lines;
+use super::util::{Countdown, GameClock};
+use super::{GameState, PixelCoord, RixelCoord, ToPlayfieldCoordinates};
+
+/// One control the tutorial teaches, in the order they come.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TutorialStep {
+    Move,
+    Dodge,
+    Collect,
+    Slowmo,
+}
+
+impl TutorialStep {
+    fn prompt(self) -> &'static str {
+        match self {
+            TutorialStep::Move => "Move around with the arrow keys",
+            TutorialStep::Dodge => "Get out of the way of the obstacle",
+            TutorialStep::Collect => "Brush past the obstacle to score",
+            TutorialStep::Slowmo => "Hold Shift while moving to slow down",
+        }
+    }
+
+    /// Shown once the player has been stuck on the step for `TUTORIAL_HINT_DELAY_MS`.
+    fn hint(self) -> &'static str {
+        match self {
+            TutorialStep::Move => "HJKL and a gamepad's stick work too",
+            TutorialStep::Dodge => "The line shows where it will come in",
+            TutorialStep::Collect => "Anything inside the outer box counts",
+            TutorialStep::Slowmo => "Slow-mo stops when its bar runs out",
+        }
+    }
+
+    fn next(self) -> Option<TutorialStep> {
+        match self {
+            TutorialStep::Move => Some(TutorialStep::Dodge),
+            TutorialStep::Dodge => Some(TutorialStep::Collect),
+            TutorialStep::Collect => Some(TutorialStep::Slowmo),
+            TutorialStep::Slowmo => None,
+        }
+    }
+}
+
+/// Walks a new player through the controls on the field itself. Obstacles only come in one at a
+/// time for the steps that need them, and can't end the run.
+pub struct Tutorial {
+    pub step: TutorialStep,
+    /// Runs out once the player has spent long enough on the step to need a hint.
+    pub hint_countdown: Countdown,
+    /// Where the player was when the step started.
+    start_pos: Vector,
+    /// The obstacle the current step is using, if it has one on the field.
+    obstacle: Option<u64>,
+    /// Whether `obstacle` has hit the player.
+    touched: bool,
+    slowmo_ticks: u32,
+}
+
+impl Tutorial {
+    fn new(step: TutorialStep, clock: &GameClock, start_pos: Vector) -> Self {
+        Tutorial {
+            step,
+            hint_countdown: Countdown::new(clock, Duration::from_millis(TUTORIAL_HINT_DELAY_MS)),
+            start_pos,
+            obstacle: None,
+            touched: false,
+            slowmo_ticks: 0,
+        }
+    }
+
+    /// Whether the tutorial has already been finished or skipped on this machine.
+    pub fn is_done() -> bool {
+        saving::load::<bool>(APP_NAME, TUTORIAL_PROFILE).unwrap_or(false)
+    }
+}
+
+impl GameState {
+    /// Start the tutorial on a fresh field. Nothing is recorded, since it isn't a real run.
+    pub(super) fn start_tutorial(&mut self) {
+        self.start_seeded_run(0);
+        self.recorder = None;
+        self.playback = None;
+        self.tutorial = Some(Tutorial::new(
+            TutorialStep::Move,
+            &self.clock,
+            self.player.rect.pos,
+        ));
+    }
+
+    /// Remember that the tutorial has been seen and go on to a normal run.
+    fn finish_tutorial(&mut self) {
+        if let Err(e) = saving::save(APP_NAME, TUTORIAL_PROFILE, &true) {
+            eprintln!("Failed to save tutorial progress: {}", e);
+        }
+        self.start_run();
+    }
+
+    /// Enter skips the rest of the tutorial.
+    pub(super) fn update_tutorial_keys(&mut self, keyboard: &Keyboard) {
+        if self.tutorial.is_some() && keyboard[Key::Return] == ButtonState::Pressed {
+            self.finish_tutorial();
+        }
+    }
+
+    /// Move on to the next step once the player has done what the current one asks.
+    pub(super) fn update_tutorial(&mut self) -> Result<()> {
+        let tutorial = match &mut self.tutorial {
+            Some(tutorial) => tutorial,
+            None => return Ok(()),
+        };
+
+        // An obstacle that has left the field is gone from the list.
+        let obstacles = &self.obstacles;
+        let obstacle = tutorial
+            .obstacle
+            .and_then(|id| obstacles.iter().find(|ob| ob.id == id));
+        if let Some(ob) = obstacle {
+            tutorial.touched |= ob.touched_player;
+        }
+        let passed = obstacle.is_none() && tutorial.obstacle.is_some();
+
+        let done = match tutorial.step {
+            TutorialStep::Move => {
+                (self.player.rect.pos - tutorial.start_pos).len() >= TUTORIAL_MOVE_DISTANCE
+            }
+            TutorialStep::Dodge => passed && !tutorial.touched,
+            TutorialStep::Collect => matches!(obstacle, Some(ob) if ob.grazed),
+            TutorialStep::Slowmo => {
+                if self.is_slowmo {
+                    tutorial.slowmo_ticks += 1;
+                }
+                tutorial.slowmo_ticks >= TUTORIAL_SLOWMO_TICKS
+            }
+        };
+
+        if done {
+            match tutorial.step.next() {
+                Some(step) => *tutorial = Tutorial::new(step, &self.clock, self.player.rect.pos),
+                None => self.finish_tutorial(),
+            }
+            return Ok(());
+        }
+
+        // Send the obstacle in again until the player gets it right.
+        let needs_obstacle = match tutorial.step {
+            TutorialStep::Dodge | TutorialStep::Collect => obstacle.is_none(),
+            _ => false,
+        };
+        if needs_obstacle {
+            tutorial.obstacle = Some(self.next_obstacle_id);
+            tutorial.touched = false;
+            self.spawn_tutorial_obstacle()?;
+        }
+
+        Ok(())
+    }
+
+    /// Drop an obstacle from the top of the field. Dodging practice aims it at the player, while
+    /// collecting practice puts it in the other half of the field so they have to go to it.
+    fn spawn_tutorial_obstacle(&mut self) -> Result<()> {
+        let step = match &self.tutorial {
+            Some(tutorial) => tutorial.step,
+            None => return Ok(()),
+        };
+        let width = 10.;
+        let player_x = self.player.rect.center().x;
+        let x = if step == TutorialStep::Dodge {
+            player_x
+        } else if player_x < FIELD_WIDTH / 2. {
+            FIELD_WIDTH * 0.75
+        } else {
+            FIELD_WIDTH * 0.25
+        };
+        let x = x.max(width / 2.).min(FIELD_WIDTH - width / 2.);
+        let rixel = RixelCoord::try_from(PixelCoord(Vector::new(x, 0.)))?;
+        let speed = self.difficulty.settings().obstacle_speed(0);
+        self.spawn_obstacle_at(rixel, speed, width, OBSTACLE_BASE_LENGTH)
+    }
+
+    /// The current step's prompt, centered under the field.
+    pub(super) fn draw_tutorial(&mut self, window: &mut Window) -> Result<()> {
+        let tutorial = match &self.tutorial {
+            Some(tutorial) => tutorial,
+            None => return Ok(()),
+        };
+        let second_line = if tutorial.hint_countdown.is_done(&self.clock) {
+            tutorial.step.hint()
+        } else {
+            "Press Enter to skip the tutorial"
+        };
+        let lines = [tutorial.step.prompt().to_string(), second_line.to_string()];
+
+        let top = self.field.height + FIELD_EDGE_BORDER_WIDTH;
+        let center = Rectangle::new((0., top), (self.field.width, 0.))
+            .on_playfield()
+            .center()
+            + Vector::new(0., FONT_SIZE_PT);
+        draw_text_lines(window, &mut self.font, &self.font_style, &lines, center)
+    }
+}
@@ -1,10 +1,24 @@
-use std::time::{Duration, Instant};
+use quicksilver::{
+    geom::{Rectangle, Shape, Vector},
+    graphics::Color,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
 
-use super::consts::system::*;
+use super::consts::{game::*, graphics::SAFE_ZONE_CELL_SIZE, system::*};
+use super::field::FieldGeometry;
+use super::graphics::lerp_color;
+use super::Obstacle;
 
 pub struct FpsGraph {
     history: [f64; FPS_GRAPH_SAMPLE_COUNT],
     i: usize,
+    /// How many slots of `history` hold real samples. Stops growing once the buffer wraps.
+    filled: usize,
 }
 
 impl FpsGraph {
@@ -12,44 +26,687 @@ impl FpsGraph {
         FpsGraph {
             history: [0.; FPS_GRAPH_SAMPLE_COUNT],
             i: 0,
+            filled: 0,
         }
     }
 
+    /// Record a sample. Quicksilver reports 0 now and then, mostly during startup; those and any
+    /// other unusable samples are dropped so they don't drag the statistics down.
     pub fn log_fps(&mut self, fps: f64) {
+        if !fps.is_normal() {
+            return;
+        }
         self.history[self.i] = fps;
         self.i = (self.i + 1) % FPS_GRAPH_SAMPLE_COUNT;
+        self.filled = (self.filled + 1).min(FPS_GRAPH_SAMPLE_COUNT);
+    }
+
+    fn samples(&self) -> &[f64] {
+        &self.history[..self.filled]
     }
 
+    /// Average of the samples logged so far, or `None` if there are none yet.
     pub fn recent_average_fps(&self) -> Option<f64> {
-        let mut sum = 0.;
-        for f in self.history.iter() {
-            if !f.is_normal() {
-                return None;
-            }
-            sum += *f;
+        if self.filled == 0 {
+            return None;
+        }
+        Some(self.samples().iter().sum::<f64>() / self.filled as f64)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.samples()
+            .iter()
+            .fold(None, |min: Option<f64>, &f| Some(min.map_or(f, |m| m.min(f))))
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.samples()
+            .iter()
+            .fold(None, |max: Option<f64>, &f| Some(max.map_or(f, |m| m.max(f))))
+    }
+
+    /// The sample `p` percent of the way up from the lowest, with `p` from 0 to 100. Uses the
+    /// nearest sample rather than interpolating.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.filled == 0 {
+            return None;
         }
-        Some(sum / self.history.len() as f64)
+        let mut sorted = self.samples().to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let rank = (p.clamp(0., 100.) / 100. * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[rank])
     }
 }
 
-pub struct Countdown {
+/// A fixed-size log that keeps only the most recent `capacity` entries. Storage is allocated up
+/// front, so pushing never allocates.
+pub struct EventLog<T> {
+    events: Vec<T>,
+    capacity: usize,
+    /// Index the next event goes in.
+    next: usize,
+}
+
+impl<T> EventLog<T> {
+    pub fn new(capacity: usize) -> Self {
+        EventLog {
+            events: Vec::with_capacity(capacity),
+            capacity,
+            next: 0,
+        }
+    }
+
+    pub fn push(&mut self, event: T) {
+        if self.events.len() < self.capacity {
+            self.events.push(event);
+        } else {
+            self.events[self.next] = event;
+        }
+        self.next = (self.next + 1) % self.capacity;
+    }
+
+    /// The logged events, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let (newer, older) = self.events.split_at(self.next);
+        older.iter().chain(newer.iter())
+    }
+}
+
+/// Tracks the largest value seen so far.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RollingMax<T: Copy + PartialOrd + Default> {
+    max: T,
+    seen: bool,
+}
+
+impl<T: Copy + PartialOrd + Default> RollingMax<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push(&mut self, val: T) {
+        if !self.seen || val > self.max {
+            self.max = val;
+            self.seen = true;
+        }
+    }
+
+    /// The largest value pushed so far, or `None` if nothing has been pushed.
+    pub fn max(&self) -> Option<T> {
+        if self.seen {
+            Some(self.max)
+        } else {
+            None
+        }
+    }
+}
+
+/// How long one frame took and what was on screen, for profiling.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameDiagnostics {
+    /// The tick the frame was updated on.
+    pub frame_id: u64,
+    pub update_duration_us: u64,
+    /// Time spent drawing since the update. Zero until the frame has been drawn.
+    pub draw_duration_us: u64,
+    pub obstacle_count: usize,
+    /// Border ripples on the field, the closest thing the game has to particles.
+    pub active_particles: usize,
+}
+
+/// The most recent `capacity` frames' diagnostics.
+pub struct DiagnosticsBuffer {
+    ring: VecDeque<FrameDiagnostics>,
+    capacity: usize,
+}
+
+impl DiagnosticsBuffer {
+    pub fn new(capacity: usize) -> Self {
+        DiagnosticsBuffer {
+            ring: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Start a new frame, dropping the oldest one if the buffer is full.
+    pub fn push(&mut self, frame: FrameDiagnostics) {
+        if self.ring.len() == self.capacity {
+            self.ring.pop_front();
+        }
+        self.ring.push_back(frame);
+    }
+
+    /// Add `us` of drawing to the newest frame. Does nothing before the first update.
+    pub fn log_draw(&mut self, us: u64) {
+        if let Some(frame) = self.ring.back_mut() {
+            frame.draw_duration_us += us;
+        }
+    }
+
+    /// The last `n` frames, newest first.
+    pub fn last(&self, n: usize) -> impl Iterator<Item = &FrameDiagnostics> {
+        self.ring.iter().rev().take(n)
+    }
+}
+
+/// Measures real time, in microseconds, from when it was started. `Instant` isn't available on
+/// the web, so there it asks the browser, which only counts whole milliseconds.
+pub struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
     start: Instant,
-    duration: Duration
+    #[cfg(target_arch = "wasm32")]
+    start_ms: f64,
+}
+
+impl Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start() -> Self {
+        Stopwatch {
+            start: Instant::now(),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn start() -> Self {
+        Stopwatch {
+            start_ms: stdweb::web::Date::now(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn elapsed_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn elapsed_us(&self) -> u64 {
+        ((stdweb::web::Date::now() - self.start_ms).max(0.) * 1000.) as u64
+    }
+}
+
+/// Buckets items by the `cell_size` square cells their rectangles cover, so finding everything
+/// near a rectangle only has to look at the cells it covers.
+pub struct SpatialGrid<T> {
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    cell_size: f32,
+    /// Every inserted item with its rectangle. Cells hold indices into this.
+    items: Vec<(T, Rectangle)>,
+}
+
+impl<T> SpatialGrid<T> {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cells: HashMap::new(),
+            cell_size,
+            items: Vec::new(),
+        }
+    }
+
+    /// Empty the grid, keeping its storage for the next round of inserts.
+    pub fn clear(&mut self) {
+        self.cells.values_mut().for_each(Vec::clear);
+        self.items.clear();
+    }
+
+    pub fn insert(&mut self, item: T, rect: &Rectangle) {
+        let index = self.items.len();
+        for cell in self.cells_covered(rect) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+        self.items.push((item, *rect));
+    }
+
+    /// Every item whose rectangle overlaps `rect`, in the order they were inserted.
+    pub fn query(&self, rect: &Rectangle) -> Vec<&T> {
+        let mut found: Vec<usize> = self
+            .cells_covered(rect)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+            .into_iter()
+            .map(|i| &self.items[i])
+            .filter(|(_, item_rect)| item_rect.overlaps_rectangle(rect))
+            .map(|(item, _)| item)
+            .collect()
+    }
+
+    /// The cells `rect` covers, edges included, so that rectangles which overlap always share at
+    /// least one cell.
+    fn cells_covered(&self, rect: &Rectangle) -> impl Iterator<Item = (i32, i32)> {
+        let cell = |v: f32| (v / self.cell_size).floor() as i32;
+        let xs = cell(rect.x())..=cell(rect.x() + rect.width());
+        let ys = cell(rect.y())..=cell(rect.y() + rect.height());
+        xs.flat_map(move |x| ys.clone().map(move |y| (x, y)))
+    }
+}
+
+/// A source of time for timers. Timers take whichever clock they should run on rather than
+/// reading the system time, so anything timed can be driven by hand.
+pub trait Clock {
+    /// Seconds since the clock started. Never decreases.
+    fn now(&self) -> f64;
+
+    /// Time elapsed since `since`, a value previously returned by `now`.
+    fn since(&self, since: f64) -> Duration {
+        Duration::from_micros(((self.now() - since).max(0.) * 1_000_000.) as u64)
+    }
+}
+
+/// A clock that only moves when it is advanced. Game time is driven by the update loop rather
+/// than `Instant`, which keeps it deterministic and makes it usable on the web.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GameClock {
+    now: f64,
+}
+
+impl GameClock {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn advance(&mut self, dt: Duration) {
+        self.now += dt.as_secs_f64();
+    }
+}
+
+impl Clock for GameClock {
+    fn now(&self) -> f64 {
+        self.now
+    }
+}
+
+/// A clock for tests. Unlike `GameClock` it can be advanced through a shared reference, so a
+/// test can move time on while a timer holds the clock.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct TestClock {
+    now: std::cell::Cell<f64>,
+}
+
+#[cfg(test)]
+impl TestClock {
+    pub fn advance(&self, dt: Duration) {
+        self.now.set(self.now.get() + dt.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+impl Clock for TestClock {
+    fn now(&self) -> f64 {
+        self.now.get()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Countdown {
+    start: f64,
+    duration: Duration,
 }
 
 impl Countdown {
-    pub fn new(duration: Duration) -> Self {
+    pub fn new(clock: &impl Clock, duration: Duration) -> Self {
         Self {
-            start: Instant::now(),
-            duration
+            start: clock.now(),
+            duration,
+        }
+    }
+
+    pub fn elapsed(&self, clock: &impl Clock) -> Duration {
+        clock.since(self.start)
+    }
+
+    pub fn is_done(&self, clock: &impl Clock) -> bool {
+        self.elapsed(clock) > self.duration
+    }
+
+    /// How far through the countdown we are, from 0 at the start to 1 once it is done.
+    pub fn fraction_elapsed(&self, clock: &impl Clock) -> f32 {
+        (self.elapsed(clock).as_secs_f32() / self.duration.as_secs_f32()).clamp(0., 1.)
+    }
+
+    /// How much of the countdown is left, from 1 at the start to 0 once it is done.
+    pub fn fraction_remaining(&self, clock: &impl Clock) -> f32 {
+        1. - self.fraction_elapsed(clock)
+    }
+
+    /// Time left until the countdown is done, or zero if it already is.
+    pub fn remaining(&self, clock: &impl Clock) -> Duration {
+        self.duration.saturating_sub(self.elapsed(clock))
+    }
+
+    /// Push the end of the countdown back by `by`.
+    pub fn extend(&mut self, by: Duration) {
+        self.duration += by;
+    }
+}
+
+/// Linear interpolation, from `self` at `t = 0` to `other` at `t = 1`.
+pub trait Lerp {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for u8 {
+    /// Saturates at the ends of `u8` if `t` goes outside 0 to 1.
+    fn lerp(&self, other: &u8, t: f32) -> u8 {
+        (*self as f32).lerp(&(*other as f32), t).round() as u8
+    }
+}
+
+impl Lerp for Vector {
+    fn lerp(&self, other: &Vector, t: f32) -> Vector {
+        Vector::new(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+    }
+}
+
+impl Lerp for Color {
+    /// `t` is clamped to 0 to 1, as in `lerp_color`.
+    fn lerp(&self, other: &Color, t: f32) -> Color {
+        lerp_color(*self, *other, t)
+    }
+}
+
+/// Advance the slow-mo stamina meter by one tick, returning the new stamina and whether it is
+/// exhausted. Stamina drains while `draining` and regenerates otherwise. Running out leaves it
+/// exhausted, which stops it draining, until it regenerates past `STAMINA_RECOVER_THRESHOLD`.
+pub fn step_stamina(stamina: f32, exhausted: bool, draining: bool) -> (f32, bool) {
+    if draining && !exhausted {
+        let stamina = (stamina - STAMINA_DRAIN_PER_TICK).max(0.);
+        (stamina, stamina <= 0.)
+    } else {
+        let stamina = (stamina + STAMINA_REGEN_PER_TICK).min(1.);
+        (stamina, exhausted && stamina < STAMINA_RECOVER_THRESHOLD)
+    }
+}
+
+/// Whether the player has stayed put for the last `CAMPING_WINDOW_MS`, given their recent
+/// `(time, position)` history, oldest first. The history has to reach back over the whole window,
+/// so a run that has only just started doesn't count as camping.
+pub fn is_camping(history: &VecDeque<(f64, Vector)>, now: f64) -> bool {
+    let window = Duration::from_millis(CAMPING_WINDOW_MS).as_secs_f64();
+    let (start, current) = match (history.front(), history.back()) {
+        (Some(&(start, _)), Some(&(_, current))) => (start, current),
+        _ => return false,
+    };
+    now - start >= window
+        && history
+            .iter()
+            .all(|&(_, pos)| (pos - current).len() <= CAMPING_RADIUS)
+}
+
+/// Step `step` places from `current` through `all`, wrapping around at either end.
+pub fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: isize) -> T {
+    let i = all.iter().position(|&x| x == current).unwrap_or(0) as isize;
+    let len = all.len() as isize;
+    all[(((i + step) % len + len) % len) as usize]
+}
+
+/// Write `score` with a comma between each group of three digits, e.g. `1,234,567`.
+pub fn format_score(score: u32) -> String {
+    let digits = score.to_string();
+    let groups: Vec<&str> = digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).unwrap())
+        .collect();
+    groups.join(",")
+}
+
+/// Find the largest axis-aligned square on the playfield that no active obstacle overlaps. The
+/// field is walked as a grid of `SAFE_ZONE_CELL_SIZE` cells; ties go to the square closest to the
+/// center of the field.
+pub fn compute_safe_zone(field: &FieldGeometry, obstacles: &[Obstacle]) -> Rectangle {
+    let (cells_x, cells_y) = (
+        (field.width / SAFE_ZONE_CELL_SIZE) as usize,
+        (field.height / SAFE_ZONE_CELL_SIZE) as usize,
+    );
+    let rects: Vec<Rectangle> = obstacles
+        .iter()
+        .filter_map(Obstacle::hitbox)
+        .collect();
+
+    // `sizes[y][x]` is the edge length (in cells) of the largest clear square whose bottom-right
+    // cell is (x, y).
+    let mut sizes = vec![vec![0usize; cells_x]; cells_y];
+    let mut best = (0, 0, 0);
+    let mut best_dist = f32::MAX;
+    let field_center = field.center();
+    for y in 0..cells_y {
+        for x in 0..cells_x {
+            let cell = Rectangle::new(
+                (x as f32 * SAFE_ZONE_CELL_SIZE, y as f32 * SAFE_ZONE_CELL_SIZE),
+                (SAFE_ZONE_CELL_SIZE, SAFE_ZONE_CELL_SIZE),
+            );
+            if rects.iter().any(|r| r.overlaps_rectangle(&cell)) {
+                continue;
+            }
+
+            sizes[y][x] = if x == 0 || y == 0 {
+                1
+            } else {
+                1 + sizes[y - 1][x].min(sizes[y][x - 1]).min(sizes[y - 1][x - 1])
+            };
+
+            let size = sizes[y][x];
+            let half = size as f32 * SAFE_ZONE_CELL_SIZE / 2.;
+            let dist = (((x + 1) as f32 * SAFE_ZONE_CELL_SIZE - half - field_center.x).powi(2)
+                + ((y + 1) as f32 * SAFE_ZONE_CELL_SIZE - half - field_center.y).powi(2))
+            .sqrt();
+            if size > best.2 || (size == best.2 && dist < best_dist) {
+                best = (x, y, size);
+                best_dist = dist;
+            }
+        }
+    }
+
+    let (x, y, size) = best;
+    let edge = size as f32 * SAFE_ZONE_CELL_SIZE;
+    Rectangle::new(
+        (
+            (x + 1) as f32 * SAFE_ZONE_CELL_SIZE - edge,
+            (y + 1) as f32 * SAFE_ZONE_CELL_SIZE - edge,
+        ),
+        (edge, edge),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Allowed float error when comparing meters and fractions.
+    const EPSILON: f32 = 1e-5;
+
+    #[test]
+    fn stamina_drains_while_in_slowmo() {
+        let (stamina, exhausted) = step_stamina(1., false, true);
+        assert!((stamina - (1. - STAMINA_DRAIN_PER_TICK)).abs() < EPSILON);
+        assert!(!exhausted);
+    }
+
+    #[test]
+    fn stamina_regenerates_up_to_full() {
+        let (stamina, exhausted) = step_stamina(0.5, false, false);
+        assert!((stamina - (0.5 + STAMINA_REGEN_PER_TICK)).abs() < EPSILON);
+        assert!(!exhausted);
+        assert_eq!(step_stamina(1., false, false), (1., false));
+    }
+
+    #[test]
+    fn running_out_of_stamina_exhausts_it() {
+        assert_eq!(step_stamina(STAMINA_DRAIN_PER_TICK / 2., false, true), (0., true));
+        // Exhausted stamina regenerates even with slow-mo held.
+        let (stamina, exhausted) = step_stamina(0., true, true);
+        assert!((stamina - STAMINA_REGEN_PER_TICK).abs() < EPSILON);
+        assert!(exhausted);
+    }
+
+    #[test]
+    fn exhaustion_lasts_until_the_recovery_threshold() {
+        let (mut stamina, mut exhausted) = (0., true);
+        while exhausted {
+            assert!(stamina < STAMINA_RECOVER_THRESHOLD);
+            (stamina, exhausted) = step_stamina(stamina, exhausted, true);
         }
+        assert!(stamina >= STAMINA_RECOVER_THRESHOLD);
+        assert!(stamina < STAMINA_RECOVER_THRESHOLD + STAMINA_REGEN_PER_TICK + EPSILON);
     }
 
-    pub fn elapsed(&self) -> Duration {
-        self.start.elapsed()
+    #[test]
+    fn fps_average_only_counts_logged_samples() {
+        let mut graph = FpsGraph::new();
+        assert_eq!(graph.recent_average_fps(), None);
+        for &fps in &[50., 55., 60., 65., 70.] {
+            graph.log_fps(fps);
+        }
+        assert_eq!(graph.recent_average_fps(), Some(60.));
     }
 
-    pub fn is_done(&self) -> bool {
-        self.start.elapsed() > self.duration
+    #[test]
+    fn countdown_fractions_run_from_one_to_zero() {
+        let mut clock = GameClock::new();
+        let countdown = Countdown::new(&clock, Duration::from_secs(2));
+        assert_eq!(countdown.fraction_remaining(&clock), 1.);
+        assert_eq!(countdown.fraction_elapsed(&clock), 0.);
+
+        clock.advance(Duration::from_secs(1));
+        assert!((countdown.fraction_remaining(&clock) - 0.5).abs() < EPSILON);
+        assert!((countdown.fraction_elapsed(&clock) - 0.5).abs() < EPSILON);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(countdown.fraction_remaining(&clock), 0.);
+        assert_eq!(countdown.fraction_elapsed(&clock), 1.);
+    }
+
+    #[test]
+    fn fps_statistics_skip_zero_samples() {
+        let mut graph = FpsGraph::new();
+        assert_eq!(graph.min(), None);
+        assert_eq!(graph.percentile(1.), None);
+        // 1 to 51 out of order, with startup zeros mixed in.
+        for i in 0..51 {
+            if i % 10 == 0 {
+                graph.log_fps(0.);
+            }
+            graph.log_fps(f64::from((i * 7) % 51 + 1));
+        }
+        assert_eq!(graph.min(), Some(1.));
+        assert_eq!(graph.max(), Some(51.));
+        assert_eq!(graph.percentile(0.), Some(1.));
+        assert_eq!(graph.percentile(50.), Some(26.));
+        assert_eq!(graph.percentile(100.), Some(51.));
+    }
+
+    #[test]
+    fn fps_one_percent_low_picks_the_slowest_frames() {
+        let mut graph = FpsGraph::new();
+        for i in 0..FPS_GRAPH_SAMPLE_COUNT {
+            graph.log_fps(if i % 32 == 7 { 20. } else { 60. });
+        }
+        assert_eq!(graph.min(), Some(20.));
+        assert_eq!(graph.max(), Some(60.));
+        assert_eq!(graph.percentile(1.), Some(20.));
+        assert_eq!(graph.percentile(50.), Some(60.));
+    }
+
+    #[test]
+    fn countdown_is_done_only_after_its_duration() {
+        let clock = TestClock::default();
+        let mut countdown = Countdown::new(&clock, Duration::from_millis(500));
+        assert!(!countdown.is_done(&clock));
+        clock.advance(Duration::from_millis(500));
+        assert!(!countdown.is_done(&clock));
+        assert_eq!(countdown.remaining(&clock), Duration::from_millis(0));
+        clock.advance(Duration::from_millis(1));
+        assert!(countdown.is_done(&clock));
+
+        countdown.extend(Duration::from_millis(100));
+        assert!(!countdown.is_done(&clock));
+        assert_eq!(countdown.remaining(&clock), Duration::from_millis(99));
+    }
+
+    #[test]
+    fn scores_are_grouped_by_thousands() {
+        assert_eq!(format_score(0), "0");
+        assert_eq!(format_score(999), "999");
+        assert_eq!(format_score(1000), "1,000");
+        assert_eq!(format_score(999_999), "999,999");
+        assert_eq!(format_score(1_000_000), "1,000,000");
+        assert_eq!(format_score(1_234_567), "1,234,567");
+        assert_eq!(format_score(u32::MAX), "4,294,967,295");
+    }
+
+    /// A position history sampled every 100 ms up to `until` seconds, taken from `pos`.
+    fn history(until: f64, pos: impl Fn(f64) -> Vector) -> VecDeque<(f64, Vector)> {
+        (0..=(until * 10.).round() as u32)
+            .map(|i| f64::from(i) / 10.)
+            .map(|t| (t, pos(t)))
+            .collect()
+    }
+
+    #[test]
+    fn standing_still_for_the_whole_window_is_camping() {
+        let window = Duration::from_millis(CAMPING_WINDOW_MS).as_secs_f64();
+        let still = history(window, |_| Vector::new(100, 100));
+        assert!(is_camping(&still, window));
+        // Drifting by less than the radius still counts.
+        let drifting = history(window, |t| Vector::new(100. + (t as f32 * 7.).sin() * 2., 100.));
+        assert!(is_camping(&drifting, window));
+    }
+
+    #[test]
+    fn a_short_history_is_not_camping() {
+        let window = Duration::from_millis(CAMPING_WINDOW_MS).as_secs_f64();
+        assert!(!is_camping(&VecDeque::new(), 0.));
+        let still = history(window / 2., |_| Vector::new(100, 100));
+        assert!(!is_camping(&still, window / 2.));
+    }
+
+    #[test]
+    fn moving_out_of_the_radius_is_not_camping() {
+        let window = Duration::from_millis(CAMPING_WINDOW_MS).as_secs_f64();
+        let walking = history(window, |t| Vector::new(100. + t as f32 * 10., 100.));
+        assert!(!is_camping(&walking, window));
+        // A single step away late in the window is enough.
+        let stepped = history(window, |t| {
+            Vector::new(if t > window - 0.5 { 110. } else { 100. }, 100.)
+        });
+        assert!(!is_camping(&stepped, window));
+    }
+
+    #[test]
+    fn grid_queries_return_all_and_only_overlapping_items() {
+        let mut rng = StdRng::seed_from_u64(97);
+        let mut random_rect = |max_size: f32| {
+            Rectangle::new(
+                (rng.gen_range(-50., 550.), rng.gen_range(-50., 550.)),
+                (rng.gen_range(0., max_size), rng.gen_range(0., max_size)),
+            )
+        };
+        let mut grid = SpatialGrid::new(64.);
+        for _ in 0..200 {
+            grid.clear();
+            let rects: Vec<Rectangle> = (0..50).map(|_| random_rect(120.)).collect();
+            for (i, rect) in rects.iter().enumerate() {
+                grid.insert(i, rect);
+            }
+            let query = random_rect(200.);
+            let expected: Vec<usize> = (0..rects.len())
+                .filter(|&i| rects[i].overlaps_rectangle(&query))
+                .collect();
+            let found: Vec<usize> = grid.query(&query).into_iter().copied().collect();
+            assert_eq!(found, expected);
+        }
     }
 }